@@ -33,7 +33,7 @@ use strict_encoding::{Sizing, TypeName, Variant, STRICT_TYPES_LIB};
 use crate::ast::ty::{Field, UnionVariants, UnnamedFields};
 use crate::ast::{EnumVariants, NamedFields, PrimitiveRef};
 use crate::typelib::LibSubref;
-use crate::{Cls, CommitConsume, TranspileRef, Ty, TypeRef};
+use crate::{tagged_hasher, Cls, CommitConsume, TranspileRef, Ty, TypeRef};
 
 /// Semantic type id, which commits to the type memory layout, name and field/variant names.
 #[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
@@ -72,24 +72,42 @@ impl Display for SemId {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_baid64(f) }
 }
 
+impl SemId {
+    /// Returns abbreviated form of the id, containing just the first chunk of its baid64
+    /// representation, like `semid:xjPyRhwn…`.
+    ///
+    /// Intended for dense human-readable output; the full [`Display`] form remains the
+    /// canonical one.
+    pub fn to_short_string(&self) -> String {
+        let full = format!("{self:-#}");
+        let chunk = full.split('-').next().unwrap_or(&full);
+        format!("{}:{chunk}…", Self::HRI)
+    }
+
+    /// Checks whether the mnemonic checksum, like the one following `#` in the alternate display
+    /// form, matches the id. The comparison ignores ASCII case.
+    pub fn verify_mnemonic(&self, mnemonic: &str) -> bool {
+        self.to_baid64_mnemonic().eq_ignore_ascii_case(mnemonic)
+    }
+}
+
 pub const SEM_ID_TAG: [u8; 32] = *b"urn:ubideco:strict-types:typ:v01";
 
 impl TypeRef for SemId {
     fn is_unicode_char(&self) -> bool { Self::unicode_char() == *self }
     fn is_byte(&self) -> bool { Self::byte() == *self || Ty::<Self>::U8.sem_id_unnamed() == *self }
+    fn is_unit(&self) -> bool { Self::unit() == *self }
 }
 
 impl PrimitiveRef for SemId {
+    fn unit() -> Self { Ty::<Self>::UNIT.sem_id_unnamed() }
     fn byte() -> Self { Ty::<Self>::BYTE.sem_id_unnamed() }
     fn unicode_char() -> Self { Ty::<Self>::UNICODE.sem_id_unnamed() }
 }
 
 impl<Ref: TypeRef> Ty<Ref> {
     fn sem_id_inner(&self, name: Option<&TypeName>) -> SemId {
-        let tag = sha2::Sha256::new_with_prefix(SEM_ID_TAG).finalize();
-        let mut hasher = sha2::Sha256::new();
-        hasher.commit_consume(tag);
-        hasher.commit_consume(tag);
+        let mut hasher = tagged_hasher(SEM_ID_TAG);
         if let Some(name) = name {
             name.sem_commit(&mut hasher);
         }
@@ -125,6 +143,10 @@ impl Ty<TranspileRef> {
     pub fn sem_id_named(&self, name: &TypeName) -> SemId { self.sem_id_inner(Some(name)) }
 }
 
+/// Commitment to the type semantics used for computing semantic and library ids.
+///
+/// Implementations must feed all multi-byte integers (lengths, sizes, counts) into the hasher in
+/// little-endian byte order, such that the ids are the same on all target platforms.
 pub trait SemCommit {
     fn sem_commit(&self, hasher: &mut impl CommitConsume);
 }
@@ -254,3 +276,40 @@ impl SemCommit for Sizing {
         hasher.commit_consume(&data);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short_string() {
+        let id = SemId::default();
+        let short = id.to_short_string();
+        assert!(short.starts_with("semid:"));
+        assert!(short.ends_with('…'));
+        assert!(id.to_string().starts_with(short.trim_end_matches('…')));
+        assert_eq!(short.chars().count(), "semid:".len() + 8 + 1);
+    }
+
+    #[test]
+    fn domain_separation() {
+        use crate::typelib::LIB_ID_TAG;
+        use crate::typesys::TYPESYS_ID_TAG;
+
+        let tags = [SEM_ID_TAG, LIB_ID_TAG, TYPESYS_ID_TAG];
+        let ids = tags.map(|tag| {
+            let mut hasher = tagged_hasher(tag);
+            hasher.commit_consume(SemId::default().as_slice());
+            hasher.finalize()
+        });
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[0], ids[2]);
+        assert_ne!(ids[1], ids[2]);
+
+        // SemId of a type and id of a library are computed with different tags
+        let ty = Ty::<SemId>::U8;
+        let mut hasher = tagged_hasher(LIB_ID_TAG);
+        ty.sem_commit(&mut hasher);
+        assert_ne!(SemId::from_byte_array(hasher.finalize()), ty.sem_id_unnamed());
+    }
+}
@@ -31,7 +31,9 @@ pub use id::{SemCommit, SemId, SEM_ID_TAG};
 pub use iter::{CheckError, IntoIter, Iter};
 pub use path::{Path, PathError, Step};
 pub use translate::Translate;
+#[cfg(test)]
+pub(crate) use ty::test::field;
 pub use ty::{
-    Cls, EnumVariants, Field, ItemCase, NamedFields, PrimitiveRef, Ty, TypeRef, UnionVariants,
-    UnnamedFields,
+    AlignedTy, Cls, CmpIgnore, EnumVariants, Field, ItemCase, MutationError, NamedFields,
+    PrimitiveRef, Ty, TypeRef, UnionVariants, UnnamedFields, VariantIter, OPTION_NONE, OPTION_SOME,
 };
@@ -72,7 +72,7 @@ impl Path {
 
     pub fn with(step: Step) -> Path { Path(small_vec!(step)) }
 
-    pub fn iter(&self) -> std::slice::Iter<Step> { self.0.iter() }
+    pub fn iter(&self) -> std::slice::Iter<'_, Step> { self.0.iter() }
 }
 
 impl<'path> IntoIterator for &'path Path {
@@ -91,10 +91,17 @@ impl Display for Path {
     }
 }
 
-#[derive(Debug, Display, Error)]
-#[display("no type path {path} exists within type {ty:?}")]
+/// Error returned by [`Ty::at_path`] when the path doesn't match the type.
+///
+/// Each collection type has exactly one step for getting to its item type: arrays use
+/// [`Step::Index`], lists - [`Step::List`], sets - [`Step::Set`] and maps - [`Step::MapKey`] or
+/// [`Step::MapValue`]. If a wrong step is used for a collection, the error explains which step
+/// is expected.
+#[derive(Debug, Error)]
 pub struct PathError<'ty, Ref: TypeRef> {
+    /// Type at which the last step of the `path` has failed.
     pub ty: &'ty Ty<Ref>,
+    /// Path up to (and including) the failed step.
     pub path: Path,
 }
 
@@ -102,16 +109,87 @@ impl<'ty, Ref: TypeRef> PathError<'ty, Ref> {
     pub fn new(ty: &'ty Ty<Ref>, path: Path) -> Self { PathError { ty, path } }
 }
 
+impl<Ref: TypeRef> Display for PathError<'_, Ref> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no type path {} exists within type {:?}", self.path, self.ty)?;
+        let expected = self.ty.item_steps();
+        match self.path.last() {
+            Some(step) if !expected.is_empty() && !expected.contains(step) => {
+                let expected = expected
+                    .iter()
+                    .map(|step| format!("`{step}`"))
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+                write!(
+                    f,
+                    "; expected {expected} step for {} type but found `{step}`",
+                    self.ty.cls()
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 impl<Ref: TypeRef> Ty<Ref> {
-    pub fn at_path(&self, path: &Path) -> Result<&Self, PathError<Ref>> {
+    /// Returns path steps leading to the item type of a collection. For non-collection types
+    /// returns an empty list.
+    pub fn item_steps(&self) -> &'static [Step] {
+        match self {
+            Ty::Array(..) => &[Step::Index],
+            Ty::List(..) => &[Step::List],
+            Ty::Set(..) => &[Step::Set],
+            Ty::Map(..) => &[Step::MapKey, Step::MapValue],
+            _ => &[],
+        }
+    }
+
+    /// Returns path step leading to the type reference at position `pos`, matching the order of
+    /// [`Ty::type_refs`].
+    pub fn step_at(&self, pos: u8) -> Option<Step> {
+        match self {
+            Ty::Struct(fields) => fields.get(pos as usize).map(|field| field.name.clone().into()),
+            Ty::Union(variants) => variants.name_by_pos(pos).cloned().map(Step::Variant),
+            Ty::Tuple(fields) if pos < fields.len_u8() => Some(Step::UnnamedField(pos)),
+            Ty::Array(..) if pos == 0 => Some(Step::Index),
+            Ty::List(..) if pos == 0 => Some(Step::List),
+            Ty::Set(..) if pos == 0 => Some(Step::Set),
+            Ty::Map(..) if pos == 0 => Some(Step::MapKey),
+            Ty::Map(..) if pos == 1 => Some(Step::MapValue),
+            _ => None,
+        }
+    }
+
+    pub fn at_path(&self, path: &Path) -> Result<&Self, PathError<'_, Ref>> {
+        self.walk_path(path, false)
+    }
+
+    /// Same as [`Ty::at_path`], but accepts any of [`Step::Index`], [`Step::List`] and
+    /// [`Step::Set`] to descend into the item type of an array, list or set. Map keys and values
+    /// still require [`Step::MapKey`] and [`Step::MapValue`].
+    ///
+    /// Useful for the paths built from data, where the kind of the collection is not known.
+    /// Prefer [`Ty::at_path`] for the paths produced from the types themselves, like the ones
+    /// from [`Ty::step_at`], since it detects mismatches between the path and the type.
+    pub fn at_path_lenient(&self, path: &Path) -> Result<&Self, PathError<'_, Ref>> {
+        self.walk_path(path, true)
+    }
+
+    fn walk_path(&self, path: &Path, lenient: bool) -> Result<&Self, PathError<'_, Ref>> {
         let mut ty = self;
-        let mut path = path.clone();
         let mut path_so_far = Path::new();
-        while let Some(step) = path.pop() {
-            let res = match (self, &step) {
+        for step in path {
+            let step_item = matches!(step, Step::Index | Step::List | Step::Set);
+            let res = match (ty, step) {
+                (Ty::Array(ty, _) | Ty::List(ty, _) | Ty::Set(ty, _), _)
+                    if lenient && step_item =>
+                {
+                    Some(ty)
+                }
                 (Ty::Struct(fields), Step::NamedField(name)) => fields.ty_by_name(name),
                 (Ty::Union(variants), Step::Variant(name)) => variants.ty_by_name(name),
                 (Ty::Struct(fields), Step::UnnamedField(tag)) => fields.ty_by_pos(*tag),
+                (Ty::Tuple(fields), Step::UnnamedField(tag)) => fields.ty_by_pos(*tag),
                 (Ty::Union(variants), Step::UnnamedField(tag)) => variants.ty_by_tag(*tag),
                 (Ty::Array(ty, _), Step::Index) => Some(ty),
                 (Ty::List(ty, _), Step::List) => Some(ty),
@@ -120,10 +198,10 @@ impl<Ref: TypeRef> Ty<Ref> {
                 (Ty::Map(_, ty, _), Step::MapValue) => Some(ty),
                 (_, _) => None,
             };
-            path_so_far.push(step).expect("confinement collection guarantees");
+            path_so_far.push(step.clone()).expect("confinement collection guarantees");
             ty = res
                 .and_then(|r| r.as_ty())
-                .ok_or_else(|| PathError::new(self, path_so_far.clone()))?
+                .ok_or_else(|| PathError::new(ty, path_so_far.clone()))?
         }
         Ok(ty)
     }
@@ -142,3 +220,45 @@ impl<Ref: TypeRef> Ty<Ref> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::Sizing;
+
+    use super::*;
+    use crate::{PrimitiveRef, TranspileRef};
+
+    #[test]
+    fn collection_steps() {
+        let array = Ty::<TranspileRef>::Array(TranspileRef::byte(), 4);
+        let list = Ty::<TranspileRef>::list(array.clone().into(), Sizing::ONE);
+
+        let path = Path::from(small_vec![Step::List, Step::Index]);
+        assert_eq!(list.at_path(&path).unwrap(), &Ty::BYTE);
+
+        let path = Path::from(small_vec![Step::List, Step::List]);
+        let err = list.at_path(&path).unwrap_err();
+        assert_eq!(err.ty, &array);
+        assert!(err.to_string().ends_with("; expected `#` step for array type but found `[]`"));
+    }
+
+    #[test]
+    fn lenient_collection_steps() {
+        let set = Ty::<TranspileRef>::set(TranspileRef::byte(), Sizing::ONE);
+        let list = Ty::<TranspileRef>::list(set.clone().into(), Sizing::ONE);
+        let map = Ty::<TranspileRef>::map(TranspileRef::byte(), list.clone().into(), Sizing::ONE);
+
+        for first in [Step::Index, Step::List, Step::Set] {
+            for second in [Step::Index, Step::List, Step::Set] {
+                let path = Path::from(small_vec![Step::MapValue, first.clone(), second]);
+                assert_eq!(map.at_path_lenient(&path).unwrap(), &Ty::BYTE);
+            }
+        }
+        let path = Path::from(small_vec![Step::MapValue, Step::Index, Step::Index]);
+        assert!(map.at_path(&path).is_err());
+
+        let path = Path::from(small_vec![Step::Index]);
+        let err = map.at_path_lenient(&path).unwrap_err();
+        assert_eq!(err.ty, &map);
+    }
+}
@@ -25,6 +25,12 @@ use std::collections::BTreeMap;
 use crate::ast::{Field, NamedFields, UnionVariants, UnnamedFields};
 use crate::{Ty, TypeRef};
 
+/// Conversion of type ASTs between different type reference representations, for instance from
+/// the transpiled form into library types, or from library types into a type system.
+///
+/// The `Builder` accumulates state shared by all translated items (like types collected into a
+/// type system), while the `Context` provides read-only information required for the
+/// translation.
 pub trait Translate<To: Sized> {
     type Context;
     type Builder;
@@ -32,6 +38,19 @@ pub trait Translate<To: Sized> {
 
     fn translate(self, builder: &mut Self::Builder, ctx: &Self::Context)
         -> Result<To, Self::Error>;
+
+    /// Translates multiple items using the same builder and context, stopping at the first
+    /// error.
+    fn translate_all(
+        items: impl IntoIterator<Item = Self>,
+        builder: &mut Self::Builder,
+        ctx: &Self::Context,
+    ) -> Result<Vec<To>, Self::Error>
+    where
+        Self: Sized,
+    {
+        items.into_iter().map(|item| item.translate(builder, ctx)).collect()
+    }
 }
 
 impl<Ref: TypeRef, ToRef: TypeRef> Translate<Ty<ToRef>> for Ty<Ref>
@@ -22,7 +22,7 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Debug, Display, Formatter};
-use std::ops::Deref;
+use std::ops::{BitOr, Deref};
 
 use amplify::confinement::Confined;
 use amplify::{confinement, Wrapper};
@@ -34,19 +34,61 @@ use strict_encoding::{
 use super::id::SemCommit;
 use crate::ast::Iter;
 
+/// Name of the variant of the canonical optional type (see [`Ty::option`]) representing a missing
+/// value.
+pub const OPTION_NONE: &str = "none";
+/// Name of the variant of the canonical optional type (see [`Ty::option`]) holding a value.
+pub const OPTION_SOME: &str = "some";
+
 /// Glue for constructing ASTs.
 pub trait TypeRef:
     SemCommit + Clone + StrictEncode + StrictDecode + StrictDumb + Eq + Debug + Sized
 {
     fn as_ty(&self) -> Option<&Ty<Self>> { None }
-    fn type_refs(&self) -> Iter<Self> { Iter::from(self) }
+    fn type_refs(&self) -> Iter<'_, Self> { Iter::from(self) }
 
     fn is_compound(&self) -> bool { false }
     fn is_byte(&self) -> bool { false }
     fn is_unicode_char(&self) -> bool { false }
+    fn is_unit(&self) -> bool { false }
+}
+
+/// Aspects of types ignored by [`Ty::eq_ignoring`]; combined with `|`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CmpIgnore {
+    /// Ignore names of structure fields and union or enum variants.
+    pub names: bool,
+    /// Ignore size bounds of lists, sets and maps.
+    pub sizing: bool,
+}
+
+impl CmpIgnore {
+    pub const NOTHING: CmpIgnore = CmpIgnore {
+        names: false,
+        sizing: false,
+    };
+    pub const NAMES: CmpIgnore = CmpIgnore {
+        names: true,
+        sizing: false,
+    };
+    pub const SIZING: CmpIgnore = CmpIgnore {
+        names: false,
+        sizing: true,
+    };
+}
+
+impl BitOr for CmpIgnore {
+    type Output = CmpIgnore;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CmpIgnore {
+            names: self.names || rhs.names,
+            sizing: self.sizing || rhs.sizing,
+        }
+    }
 }
 
 pub trait PrimitiveRef: TypeRef {
+    fn unit() -> Self;
     fn byte() -> Self;
     fn unicode_char() -> Self;
 }
@@ -139,13 +181,26 @@ pub enum Ty<Ref: TypeRef> {
     Array(Ref, u16),
 
     #[strict_type(tag = 8)]
-    List(Ref, Sizing),
+    List(
+        Ref,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::util::deserialize_sizing"))]
+        Sizing,
+    ),
 
     #[strict_type(tag = 9)]
-    Set(Ref, Sizing),
+    Set(
+        Ref,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::util::deserialize_sizing"))]
+        Sizing,
+    ),
 
     #[strict_type(tag = 10)]
-    Map(Ref, Ref, Sizing),
+    Map(
+        Ref,
+        Ref,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::util::deserialize_sizing"))]
+        Sizing,
+    ),
 }
 
 impl<Ref: TypeRef> Ty<Ref> {
@@ -185,11 +240,13 @@ impl<Ref: TypeRef> Ty<Ref> {
 
     pub const UNICODE: Ty<Ref> = Ty::UnicodeChar;
 
+    pub fn primitive(prim: Primitive) -> Self { Ty::Primitive(prim) }
     pub fn enumerate(variants: EnumVariants) -> Self { Ty::Enum(variants) }
     pub fn union(variants: UnionVariants<Ref>) -> Self { Ty::Union(variants) }
     pub fn struc(fields: NamedFields<Ref>) -> Self { Ty::Struct(fields) }
     pub fn tuple(fields: UnnamedFields<Ref>) -> Self { Ty::Tuple(fields) }
 
+    pub fn array(ty: Ref, len: u16) -> Self { Ty::Array(ty, len) }
     pub fn list(ty: Ref, sizing: Sizing) -> Self { Ty::List(ty, sizing) }
     pub fn set(ty: Ref, sizing: Sizing) -> Self { Ty::Set(ty, sizing) }
     pub fn map(key: Ref, val: Ref, sizing: Sizing) -> Self { Ty::Map(key, val, sizing) }
@@ -216,16 +273,45 @@ impl<Ref: TypeRef> Ty<Ref> {
         matches!(self, Ty::Array(..) | Ty::List(..) | Ty::Set(..) | Ty::Map(..))
     }
 
+    pub fn is_enum(&self) -> bool { matches!(self, Ty::Enum(_)) }
+    pub fn is_union(&self) -> bool { matches!(self, Ty::Union(_)) }
+    /// Returns iterator over variants of enum or union type, ordered by their tags.
+    pub fn as_enum_or_union(&self) -> Option<VariantIter<'_, Ref>> {
+        match self {
+            Ty::Enum(variants) => Some(VariantIter::Enum(variants.iter())),
+            Ty::Union(variants) => Some(VariantIter::Union(variants.keys())),
+            _ => None,
+        }
+    }
+
     pub fn is_newtype(&self) -> bool { matches!(self, Ty::Tuple(fields) if fields.len() == 1) }
     pub fn is_byte_array(&self) -> bool { matches!(self, Ty::Array(ty, _) if ty.is_byte()) }
+    /// Returns length of a fixed-size byte array, or `None` if the type is not a byte array.
+    pub fn as_byte_array(&self) -> Option<u16> {
+        match self {
+            Ty::Array(ty, len) if ty.is_byte() => Some(*len),
+            _ => None,
+        }
+    }
+    /// Returns bounds of a variable-length byte string, or `None` if the type is not a list of
+    /// bytes.
+    pub fn as_byte_string(&self) -> Option<Sizing> {
+        match self {
+            Ty::List(ty, sizing) if ty.is_byte() => Some(*sizing),
+            _ => None,
+        }
+    }
+    /// Detects whether the type is the canonical optional type (see [`Ty::option`]).
     pub fn is_option(&self) -> bool { self.as_some().is_some() }
+    /// Returns the type wrapped by the canonical optional type (see [`Ty::option`]), or `None`
+    /// for any other type.
     pub fn as_some(&self) -> Option<&Ref> {
         match self {
             Ty::Union(variants)
                 if variants.len() == 2
-                    && &variants.unwrap_first().name == &vname!("none")
+                    && variants.unwrap_first().name.as_str() == OPTION_NONE
                     && variants.unwrap_first().tag == 0
-                    && &variants.unwrap_last().name == &vname!("some")
+                    && variants.unwrap_last().name.as_str() == OPTION_SOME
                     && variants.unwrap_last().tag == 1 =>
             {
                 Some(variants.last_key_value().unwrap().1)
@@ -234,6 +320,104 @@ impl<Ref: TypeRef> Ty<Ref> {
         }
     }
 
+    /// Returns names of the structure or tuple fields, or union variants, in their ordinal
+    /// order. Tuple fields are unnamed and are represented with `None`.
+    ///
+    /// For other types returns `None`.
+    pub fn field_names(&self) -> Option<Vec<Option<FieldName>>> {
+        match self {
+            Ty::Struct(fields) => {
+                Some(fields.iter().map(|field| Some(field.name.clone())).collect())
+            }
+            Ty::Tuple(fields) => Some(vec![None; fields.len()]),
+            Ty::Union(variants) => Some(
+                variants
+                    .keys()
+                    .map(|variant| Some(FieldName::from_inner(variant.name.to_inner())))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Detects whether the type has several fields or variants with the same name.
+    pub fn has_duplicate_field_names(&self) -> bool {
+        let Some(names) = self.field_names() else {
+            return false;
+        };
+        let mut set = BTreeSet::new();
+        !names.into_iter().flatten().all(|name| set.insert(name))
+    }
+
+    /// Compares two types ignoring the order of structure fields: structures are considered
+    /// equal if they have the same set of field names, with each field having the same type.
+    /// All other types, including tuples, are compared with strict equality.
+    ///
+    /// This is a semantic comparison and not a check of wire compatibility: reordering
+    /// structure fields changes both the encoding and the semantic id of the type.
+    pub fn eq_unordered_named(&self, other: &Ty<Ref>) -> bool {
+        match (self, other) {
+            (Ty::Struct(a), Ty::Struct(b)) => {
+                a.len() == b.len()
+                    && !self.has_duplicate_field_names()
+                    && a.iter().all(|field| b.ty_by_name(&field.name) == Some(&field.ty))
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Compares two types ignoring collection size bounds, such that `[Byte ^ 1..10]` and
+    /// `[Byte ^ 1..20]` have the same shape. Same as [`Ty::eq_ignoring`] with
+    /// [`CmpIgnore::SIZING`].
+    pub fn same_shape(&self, other: &Ty<Ref>) -> bool { self.eq_ignoring(other, CmpIgnore::SIZING) }
+
+    /// Compares two types ignoring names of structure fields and union or enum variants, which
+    /// are then matched by their position and tag. Same as [`Ty::eq_ignoring`] with
+    /// [`CmpIgnore::NAMES`].
+    pub fn equivalent(&self, other: &Ty<Ref>) -> bool { self.eq_ignoring(other, CmpIgnore::NAMES) }
+
+    /// Compares two types ignoring the aspects specified by `ignore`.
+    ///
+    /// Inline types are compared recursively; references to other types are compared with
+    /// strict equality, since semantic ids commit to both names and size bounds.
+    pub fn eq_ignoring(&self, other: &Ty<Ref>, ignore: CmpIgnore) -> bool {
+        let refs = |a: &Ref, b: &Ref| match (a.as_ty(), b.as_ty()) {
+            (Some(a), Some(b)) => a.eq_ignoring(b, ignore),
+            _ => a == b,
+        };
+        let variants =
+            |a: &Variant, b: &Variant| a.tag == b.tag && (ignore.names || a.name == b.name);
+        let sizing = |a: &Sizing, b: &Sizing| ignore.sizing || a == b;
+        match (self, other) {
+            (Ty::Enum(a), Ty::Enum(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| variants(a, b))
+            }
+            (Ty::Union(a), Ty::Union(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((va, a), (vb, b))| variants(va, vb) && refs(a, b))
+            }
+            (Ty::Struct(a), Ty::Struct(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|(a, b)| (ignore.names || a.name == b.name) && refs(&a.ty, &b.ty))
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| refs(a, b))
+            }
+            (Ty::Array(a, len_a), Ty::Array(b, len_b)) => len_a == len_b && refs(a, b),
+            (Ty::List(a, sa), Ty::List(b, sb)) | (Ty::Set(a, sa), Ty::Set(b, sb)) => {
+                sizing(sa, sb) && refs(a, b)
+            }
+            (Ty::Map(ka, va, sa), Ty::Map(kb, vb, sb)) => {
+                sizing(sa, sb) && refs(ka, kb) && refs(va, vb)
+            }
+            _ => self == other,
+        }
+    }
+
     pub fn as_wrapped_ty(&self) -> Option<&Ty<Ref>> {
         if let Ty::Tuple(fields) = self {
             if fields.len() == 1 {
@@ -244,6 +428,75 @@ impl<Ref: TypeRef> Ty<Ref> {
         }
         None
     }
+
+    /// Normalizes the type by collapsing inline wrappers which do not affect the wire format:
+    /// - newtype tuples and single-field structures are replaced with the wrapped type;
+    /// - arrays of a single item are replaced with the item type.
+    ///
+    /// The normalization is shallow: it collapses only the chain of wrappers at the top of the
+    /// type, descending into a wrapped type if it is inline (i.e. [`TypeRef::as_ty`] returns a
+    /// value). Types referenced by their id, as well as inline types nested in the fields,
+    /// variants or items of a type which is not collapsed, are left intact.
+    ///
+    /// Unions and enums are never collapsed, even when they have a single variant, since their
+    /// encoding always includes a variant tag byte.
+    ///
+    /// Any applied normalization changes the [`SemId`](crate::SemId) of the type, since the
+    /// semantic id commits to the type class and field names; the normalized type has the same
+    /// id as the original one only if no normalization was performed. Thus, normalized types
+    /// must be used only for comparing the wire format and never as a replacement of the
+    /// original type definition.
+    pub fn normalize(&self) -> Ty<Ref> {
+        let inner = match self {
+            Ty::Tuple(fields) if fields.len() == 1 => fields.first(),
+            Ty::Struct(fields) if fields.len() == 1 => fields.first().map(|field| &field.ty),
+            Ty::Array(ty, 1) => Some(ty),
+            _ => None,
+        };
+        match inner.and_then(Ref::as_ty) {
+            Some(ty) => ty.normalize(),
+            None => self.clone(),
+        }
+    }
+}
+
+/// Column at which type definitions start in library listings with the default type name width:
+/// `data `, 17 characters of the name and ` : `.
+const DEFAULT_COLUMN: usize = 25;
+
+/// Writes a line break followed by the separator of the members continuing a multi-line type,
+/// such that the members are aligned with the first one starting at `column`.
+fn write_continuation(f: &mut Formatter<'_>, column: usize, sep: char) -> fmt::Result {
+    write!(f, "\n{:1$}{sep} ", "", column.saturating_sub(2))
+}
+
+/// Type displayed with multi-line members aligned to a given column (see [`Ty::display_at`]).
+pub struct AlignedTy<'ty, Ref: TypeRef> {
+    ty: &'ty Ty<Ref>,
+    column: usize,
+}
+
+impl<'ty, Ref: TypeRef> Display for AlignedTy<'ty, Ref>
+where Ref: Display
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.ty.fmt_at(f, self.column) }
+}
+
+impl<Ref: TypeRef> Ty<Ref>
+where Ref: Display
+{
+    /// Displays the type starting at the given `column` of a line. Structures, unions and enums
+    /// which are displayed over several lines have their members aligned under the first one.
+    pub fn display_at(&self, column: usize) -> AlignedTy<'_, Ref> { AlignedTy { ty: self, column } }
+
+    fn fmt_at(&self, f: &mut Formatter<'_>, column: usize) -> fmt::Result {
+        match self {
+            Ty::Enum(vars) => vars.fmt_at(f, column),
+            Ty::Union(fields) if !self.is_option() => fields.fmt_at(f, column),
+            Ty::Struct(fields) => fields.fmt_at(f, column),
+            _ => Display::fmt(self, f),
+        }
+    }
 }
 
 impl<Ref: TypeRef> Display for Ty<Ref>
@@ -252,14 +505,14 @@ where Ref: Display
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Ty::Primitive(prim) => Display::fmt(prim, f),
-            Ty::Enum(vars) => Display::fmt(vars, f),
+            Ty::Enum(vars) => vars.fmt_at(f, DEFAULT_COLUMN),
             Ty::Union(fields) if self.is_option() => {
                 let variant = fields.get(&Variant::some()).expect("optional");
                 Display::fmt(variant, f)?;
                 f.write_str("?")
             }
-            Ty::Union(fields) => Display::fmt(fields, f),
-            Ty::Struct(fields) => Display::fmt(fields, f),
+            Ty::Union(fields) => fields.fmt_at(f, DEFAULT_COLUMN),
+            Ty::Struct(fields) => fields.fmt_at(f, DEFAULT_COLUMN),
             Ty::Tuple(fields) => Display::fmt(fields, f),
             Ty::Array(ty, len) => {
                 f.write_str("[")?;
@@ -308,6 +561,19 @@ pub enum ItemCase {
     MapValue,
 }
 
+impl Display for ItemCase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemCase::UnnamedField(pos) => write!(f, "{pos}"),
+            ItemCase::NamedField(_, name) => write!(f, "{name}"),
+            ItemCase::UnionVariant(_, name) => write!(f, "{name}"),
+            ItemCase::ArrayItem | ItemCase::ListItem | ItemCase::SetItem => f.write_str("[]"),
+            ItemCase::MapKey => f.write_str("{key}"),
+            ItemCase::MapValue => f.write_str("{value}"),
+        }
+    }
+}
+
 impl<Ref: TypeRef> Ty<Ref> {
     pub fn ty_at(&self, pos: u8) -> Option<&Ref> {
         match self {
@@ -343,6 +609,101 @@ impl<Ref: TypeRef> Ty<Ref> {
     pub fn is_unicode_char(&self) -> bool { matches!(self, x if x == &Ty::UNICODE) }
 }
 
+/// Errors happening during in-place modification of a type.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum MutationError {
+    /// sizing can't be set for a {0} type, which is not a list, set or map.
+    NoSizing(Cls),
+
+    /// invalid sizing with minimum {0} exceeding maximum {1}.
+    InvalidSizing(u64, u64),
+
+    /// fields can be renamed only in structures, while the type is {0}.
+    NotStruct(Cls),
+
+    /// structure has no field named `{0}`.
+    UnknownField(FieldName),
+
+    /// structure already has a field named `{0}`.
+    DuplicateField(FieldName),
+}
+
+/// In-place modifications of types.
+///
+/// Since the semantic id commits to the full type definition, any modification changes the
+/// [`crate::SemId`] of the type and of all types referencing it.
+impl<Ref: TypeRef> Ty<Ref> {
+    /// Changes the limits on the number of items of a list, set or map, returning the previous
+    /// sizing.
+    pub fn set_sizing(&mut self, sizing: Sizing) -> Result<Sizing, MutationError> {
+        if sizing.min > sizing.max {
+            return Err(MutationError::InvalidSizing(sizing.min, sizing.max));
+        }
+        match self {
+            Ty::List(_, old) | Ty::Set(_, old) | Ty::Map(_, _, old) => {
+                Ok(std::mem::replace(old, sizing))
+            }
+            _ => Err(MutationError::NoSizing(self.cls())),
+        }
+    }
+
+    /// Renames a structure field, keeping its position and type.
+    pub fn rename_field(&mut self, from: &FieldName, to: FieldName) -> Result<(), MutationError> {
+        let cls = self.cls();
+        let Ty::Struct(fields) = self else {
+            return Err(MutationError::NotStruct(cls));
+        };
+        if from != &to && fields.iter().any(|field| field.name == to) {
+            return Err(MutationError::DuplicateField(to));
+        }
+        let field = fields
+            .0
+            .iter_mut()
+            .find(|field| &field.name == from)
+            .ok_or_else(|| MutationError::UnknownField(from.clone()))?;
+        field.name = to;
+        Ok(())
+    }
+}
+
+impl<Ref: PrimitiveRef> Ty<Ref> {
+    /// Constructs canonical optional type, matching the encoding of rust `Option<T>`: a union
+    /// with `none` unit variant tagged `0` and `some` variant tagged `1`.
+    pub fn option(some: Ref) -> Self {
+        let variants = Confined::try_from(bmap! {
+            Variant::none() => Ref::unit(),
+            Variant::some() => some,
+        })
+        .expect("two variants always fit");
+        Ty::Union(UnionVariants(variants))
+    }
+
+    /// Converts union, all variants of which have unit type, into an enum. Enums are returned
+    /// as is; other types (including unions with non-unit variants) are returned as an error.
+    pub fn try_into_enum(self) -> Result<Self, Self> {
+        match self {
+            Ty::Union(variants) if variants.values().all(|ty| ty == &Ref::unit()) => {
+                let variants = Confined::from_collection_unsafe(variants.into_keys().collect());
+                Ok(Ty::Enum(EnumVariants(variants)))
+            }
+            Ty::Enum(_) => Ok(self),
+            _ => Err(self),
+        }
+    }
+
+    /// Constructs optional type like [`Ty::option`], returning `None` if the wrapped type is
+    /// already known to be optional, preventing double wrapping.
+    ///
+    /// Only types embedded into the reference (see [`TypeRef::as_ty`]) can be checked.
+    pub fn optional(some: Ref) -> Option<Self> {
+        if some.as_ty().map(Ty::is_option).unwrap_or_default() {
+            return None;
+        }
+        Some(Self::option(some))
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
@@ -362,6 +723,10 @@ where Ref: Display
     }
 }
 
+/// Named fields of a structure type.
+///
+/// Fields carry no ordinals: iteration yields them in the declaration order, which is also the
+/// order in which they are strict-encoded.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, dumb = fields!("dumb" => Ref::strict_dumb()))]
@@ -422,14 +787,20 @@ impl<Ref: TypeRef> NamedFields<Ref> {
 impl<Ref: TypeRef> Display for NamedFields<Ref>
 where Ref: Display
 {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_at(f, DEFAULT_COLUMN) }
+}
+
+impl<Ref: TypeRef> NamedFields<Ref>
+where Ref: Display
+{
+    fn fmt_at(&self, f: &mut Formatter<'_>, column: usize) -> fmt::Result {
         let len = self.len();
         let mut iter = self.iter();
         let last = iter.next_back();
         for field in iter {
             Display::fmt(field, f)?;
             if len >= 3 {
-                f.write_str("\n                       , ")?;
+                write_continuation(f, column, ',')?;
             } else {
                 f.write_str(", ")?;
             }
@@ -441,6 +812,7 @@ where Ref: Display
     }
 }
 
+/// Unnamed fields of a tuple type, iterated in the declaration (and encoding) order.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, dumb = fields!(Ref::strict_dumb()))]
@@ -512,6 +884,11 @@ where Ref: Display
     }
 }
 
+/// Variants of a union type.
+///
+/// Variants are ordered by their tags, so iteration always yields them in ascending tag order
+/// regardless of the order in which they were inserted. Positional accessors like
+/// [`UnionVariants::ty_by_pos`] rely on this guarantee.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
 #[derive(StrictDumb, StrictType)]
 #[strict_type(lib = STRICT_TYPES_LIB, dumb = variants!("dumb" => Ref::strict_dumb()))]
@@ -563,8 +940,8 @@ impl<'a, Ref: TypeRef> IntoIterator for &'a UnionVariants<Ref> {
 impl<Ref: TypeRef> UnionVariants<Ref> {
     pub fn into_inner(self) -> BTreeMap<Variant, Ref> { self.0.into_inner() }
 
-    pub fn unwrap_first(&self) -> &Variant { &self.0.first_key_value().unwrap().0 }
-    pub fn unwrap_last(&self) -> &Variant { &self.0.last_key_value().unwrap().0 }
+    pub fn unwrap_first(&self) -> &Variant { self.0.first_key_value().unwrap().0 }
+    pub fn unwrap_last(&self) -> &Variant { self.0.last_key_value().unwrap().0 }
 
     pub fn into_keys(self) -> std::collections::btree_map::IntoKeys<Variant, Ref> {
         self.0.into_inner().into_keys()
@@ -585,6 +962,11 @@ impl<Ref: TypeRef> UnionVariants<Ref> {
         self.0.iter().find(|(v, _)| v.tag == tag).map(|(_, ty)| ty)
     }
     pub fn ty_by_pos(&self, pos: u8) -> Option<&Ref> { self.0.values().nth(pos as usize) }
+    /// Detects whether the variant with the given tag carries data, i.e. its type is not a unit.
+    /// Returns `None` if there is no such variant.
+    pub fn has_payload(&self, tag: u8) -> Option<bool> {
+        self.ty_by_tag(tag).map(|ty| !ty.is_unit())
+    }
     pub fn tag_by_name(&self, name: &VariantName) -> Option<u8> {
         self.0.keys().find(|v| &v.name == name).map(|v| v.tag)
     }
@@ -599,7 +981,13 @@ impl<Ref: TypeRef> UnionVariants<Ref> {
 impl<Ref: TypeRef> Display for UnionVariants<Ref>
 where Ref: Display
 {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_at(f, DEFAULT_COLUMN) }
+}
+
+impl<Ref: TypeRef> UnionVariants<Ref>
+where Ref: Display
+{
+    fn fmt_at(&self, f: &mut Formatter<'_>, column: usize) -> fmt::Result {
         let mut iter = self.iter();
         let last = iter.next_back();
         let mut last_tag = 0u8;
@@ -619,7 +1007,7 @@ where Ref: Display
             } else {
                 Display::fmt(ty, f)?;
             }
-            write!(f, "\n                       | ")?;
+            write_continuation(f, column, '|')?;
         }
         if let Some((variant, ty)) = last {
             write!(f, "{variant}")?;
@@ -641,6 +1029,7 @@ where Ref: Display
     }
 }
 
+/// Variants of an enum type, iterated in ascending tag order (see [`UnionVariants`]).
 #[derive(Wrapper, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
 #[wrapper(Deref)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
@@ -686,8 +1075,39 @@ impl EnumVariants {
     pub fn has_tag(&self, tag: u8) -> bool { self.0.iter().any(|v| v.tag == tag) }
 }
 
+/// Iterator over variants of enum or union type, returned by [`Ty::as_enum_or_union`].
+#[derive(Clone, Debug)]
+pub enum VariantIter<'ty, Ref: TypeRef> {
+    Enum(std::collections::btree_set::Iter<'ty, Variant>),
+    Union(std::collections::btree_map::Keys<'ty, Variant, Ref>),
+}
+
+impl<'ty, Ref: TypeRef> Iterator for VariantIter<'ty, Ref> {
+    type Item = &'ty Variant;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            VariantIter::Enum(iter) => iter.next(),
+            VariantIter::Union(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            VariantIter::Enum(iter) => iter.size_hint(),
+            VariantIter::Union(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'ty, Ref: TypeRef> ExactSizeIterator for VariantIter<'ty, Ref> {}
+
 impl Display for EnumVariants {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_at(f, DEFAULT_COLUMN) }
+}
+
+impl EnumVariants {
+    fn fmt_at(&self, f: &mut Formatter<'_>, column: usize) -> fmt::Result {
         let mut iter = self.iter();
         let mut last_tag = 0;
         if let Some(variant) = iter.next() {
@@ -712,8 +1132,285 @@ impl Display for EnumVariants {
             if iter.len() == 0 {
                 break;
             }
-            write!(f, "\n                      ")?;
+            write!(f, "\n{:1$}", "", column.saturating_sub(3))?;
         }
         writeln!(f)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::typelib::TranspileRef;
+    use crate::SemId;
+
+    pub(crate) fn field<Ref: TypeRef>(name: &'static str, ty: Ref) -> Field<Ref> {
+        Field {
+            name: fname!(name),
+            ty,
+        }
+    }
+
+    #[test]
+    fn option() {
+        let byte = SemId::byte();
+        let opt = Ty::<SemId>::option(byte);
+        assert!(opt.is_option());
+        assert_eq!(opt.as_some(), Some(&byte));
+        assert_eq!(Variant::none().name.as_str(), OPTION_NONE);
+        assert_eq!(Variant::some().name.as_str(), OPTION_SOME);
+        assert_eq!(Ty::optional(byte), Some(opt));
+
+        let inner = TranspileRef::from(Ty::option(TranspileRef::byte()));
+        assert!(Ty::optional(TranspileRef::byte()).is_some());
+        assert_eq!(Ty::optional(inner), None);
+    }
+
+    #[test]
+    fn enum_union() {
+        let opt = Ty::<SemId>::option(SemId::byte());
+        assert!(opt.is_union() && !opt.is_enum());
+        assert_eq!(opt.as_enum_or_union().unwrap().collect::<Vec<_>>(), vec![
+            &Variant::none(),
+            &Variant::some()
+        ]);
+        assert_eq!(opt.clone().try_into_enum(), Err(opt));
+
+        let units = Ty::<SemId>::Union(UnionVariants(
+            Confined::try_from(bmap! {
+                Variant::none() => SemId::unit(),
+                Variant::some() => SemId::unit(),
+            })
+            .unwrap(),
+        ));
+        let en = units.try_into_enum().unwrap();
+        assert!(en.is_enum());
+        assert_eq!(en.as_enum_or_union().map(|iter| iter.len()), Some(2));
+        assert_eq!(en.as_enum_or_union().unwrap().collect::<Vec<_>>(), vec![
+            &Variant::none(),
+            &Variant::some()
+        ]);
+        assert_eq!(en.clone().try_into_enum(), Ok(en));
+        assert!(Ty::<SemId>::U8.as_enum_or_union().is_none());
+    }
+
+    #[test]
+    fn constructors() {
+        let fields = NamedFields::try_from(vec![
+            Field {
+                name: fname!("id"),
+                ty: TranspileRef::from(Ty::array(TranspileRef::byte(), 32)),
+            },
+            Field {
+                name: fname!("amount"),
+                ty: TranspileRef::from(Ty::primitive(Primitive::U64)),
+            },
+        ])
+        .unwrap();
+        let ty = Ty::struc(fields);
+        assert_eq!(ty.to_string(), "id [Byte ^ 32], amount U64");
+    }
+
+    #[test]
+    fn mutation() {
+        let mut list = Ty::<SemId>::list(SemId::byte(), Sizing::U8);
+        let id = list.sem_id_unnamed();
+        assert_eq!(list.set_sizing(Sizing::U16), Ok(Sizing::U8));
+        assert_ne!(list.sem_id_unnamed(), id);
+        assert_eq!(list.set_sizing(Sizing::new(2, 1)), Err(MutationError::InvalidSizing(2, 1)));
+        assert_eq!(
+            Ty::<SemId>::primitive(Primitive::U8).set_sizing(Sizing::U8),
+            Err(MutationError::NoSizing(Cls::Primitive))
+        );
+
+        let fields = vec![field("a", SemId::byte()), field("b", SemId::byte())];
+        let mut ty = Ty::<SemId>::struc(NamedFields::try_from(fields).unwrap());
+        assert_eq!(
+            ty.rename_field(&fname!("a"), fname!("b")),
+            Err(MutationError::DuplicateField(fname!("b")))
+        );
+        assert_eq!(
+            ty.rename_field(&fname!("c"), fname!("d")),
+            Err(MutationError::UnknownField(fname!("c")))
+        );
+        ty.rename_field(&fname!("a"), fname!("c")).unwrap();
+        assert_eq!(ty.field_names(), Some(vec![Some(fname!("c")), Some(fname!("b"))]));
+    }
+
+    #[test]
+    fn field_names() {
+        let struc = |fields| Ty::<SemId>::struc(NamedFields::try_from(fields).unwrap());
+        let unique = struc(vec![field("a", SemId::byte()), field("b", SemId::unit())]);
+        assert_eq!(unique.field_names(), Some(vec![Some(fname!("a")), Some(fname!("b"))]));
+        assert!(!unique.has_duplicate_field_names());
+        let dup = struc(vec![field("a", SemId::byte()), field("a", SemId::unit())]);
+        assert_eq!(dup.field_names(), Some(vec![Some(fname!("a")), Some(fname!("a"))]));
+        assert!(dup.has_duplicate_field_names());
+
+        let tuple = Ty::<SemId>::tuple(
+            UnnamedFields::try_from(vec![SemId::byte(), SemId::byte()]).unwrap(),
+        );
+        assert_eq!(tuple.field_names(), Some(vec![None, None]));
+        assert!(!tuple.has_duplicate_field_names());
+
+        let option = Ty::<SemId>::option(SemId::byte());
+        assert_eq!(option.field_names(), Some(vec![Some(fname!("none")), Some(fname!("some"))]));
+        assert!(!option.has_duplicate_field_names());
+
+        assert_eq!(Ty::<SemId>::U8.field_names(), None);
+        assert!(!Ty::<SemId>::U8.has_duplicate_field_names());
+        let en = Ty::<SemId>::enumerate(
+            EnumVariants::try_from(bset! { Variant::named(0, vname!("a")) }).unwrap(),
+        );
+        assert_eq!(en.field_names(), None);
+    }
+
+    #[test]
+    fn variants_tag_order() {
+        let variant = |tag: u8, name: &'static str| Variant::named(tag, vname!(name));
+        let union = UnionVariants::try_from(bmap! {
+            variant(2, "c") => SemId::byte(),
+            variant(0, "a") => SemId::unit(),
+            variant(1, "b") => SemId::unicode_char(),
+        })
+        .unwrap();
+        assert_eq!(union.keys().map(|v| v.tag).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(union.ty_by_pos(0), Some(&SemId::unit()));
+        assert_eq!(union.name_by_pos(2), Some(&vname!("c")));
+
+        let enumer =
+            EnumVariants::try_from(bset! { variant(2, "c"), variant(0, "a"), variant(1, "b") })
+                .unwrap();
+        assert_eq!(enumer.iter().map(|v| v.tag).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn eq_unordered_named() {
+        let struc = |fields| Ty::<SemId>::struc(NamedFields::try_from(fields).unwrap());
+        let a = struc(vec![field("a", SemId::byte()), field("b", SemId::unit())]);
+        let b = struc(vec![field("b", SemId::unit()), field("a", SemId::byte())]);
+        assert_ne!(a, b);
+        assert!(a.eq_unordered_named(&b));
+        assert!(b.eq_unordered_named(&a));
+
+        let c = struc(vec![field("a", SemId::unit()), field("b", SemId::byte())]);
+        assert!(!a.eq_unordered_named(&c));
+        let d = struc(vec![field("a", SemId::byte())]);
+        assert!(!a.eq_unordered_named(&d));
+        let dup = struc(vec![field("a", SemId::byte()), field("a", SemId::byte())]);
+        let other = struc(vec![field("a", SemId::byte()), field("c", SemId::byte())]);
+        assert!(!dup.eq_unordered_named(&other));
+
+        let tuple = |fields| Ty::<SemId>::tuple(UnnamedFields::try_from(fields).unwrap());
+        let t1 = tuple(vec![SemId::byte(), SemId::unit()]);
+        let t2 = tuple(vec![SemId::unit(), SemId::byte()]);
+        assert!(t1.eq_unordered_named(&t1.clone()));
+        assert!(!t1.eq_unordered_named(&t2));
+    }
+
+    #[test]
+    fn has_payload() {
+        let Ty::Union(variants) = Ty::<SemId>::option(SemId::byte()) else {
+            unreachable!()
+        };
+        assert_eq!(variants.has_payload(0), Some(false));
+        assert_eq!(variants.has_payload(1), Some(true));
+        assert_eq!(variants.has_payload(2), None);
+
+        let Ty::Union(variants) = Ty::<TranspileRef>::option(TranspileRef::Named(tn!("Foo")))
+        else {
+            unreachable!()
+        };
+        assert_eq!(variants.has_payload(0), Some(false));
+        assert_eq!(variants.has_payload(1), Some(true));
+    }
+
+    #[test]
+    fn eq_ignoring() {
+        let list = |max| Ty::<SemId>::list(SemId::byte(), Sizing::new(1, max));
+        assert!(list(10).same_shape(&list(20)));
+        assert!(!list(10).equivalent(&list(20)));
+        assert!(!list(10).same_shape(&Ty::set(SemId::byte(), Sizing::new(1, 10))));
+
+        let struc = |a, b, max| {
+            Ty::<SemId>::struc(
+                NamedFields::try_from(vec![
+                    field(a, SemId::byte()),
+                    field(b, list(max).sem_id_unnamed()),
+                ])
+                .unwrap(),
+            )
+        };
+        let a = struc("a", "b", 10);
+        assert!(a.equivalent(&struc("c", "d", 10)));
+        assert!(!a.same_shape(&struc("c", "d", 10)));
+        // named references commit to the sizing of the referenced types
+        assert!(!a.eq_ignoring(&struc("c", "d", 20), CmpIgnore::NAMES | CmpIgnore::SIZING));
+
+        let union = |names: [&'static str; 2], max| {
+            let map = names
+                .into_iter()
+                .enumerate()
+                .map(|(tag, name)| {
+                    (Variant::named(tag as u8, vname!(name)), list(max).sem_id_unnamed())
+                })
+                .collect::<BTreeMap<_, _>>();
+            Ty::<SemId>::union(UnionVariants::try_from(map).unwrap())
+        };
+        assert!(union(["a", "b"], 10).equivalent(&union(["c", "d"], 10)));
+        assert!(!union(["a", "b"], 10).eq_ignoring(&union(["c", "d"], 10), CmpIgnore::NOTHING));
+        assert!(!union(["a", "b"], 10).same_shape(&union(["b", "a"], 10)));
+
+        let a = Ty::<SemId>::enumerate(
+            EnumVariants::try_from(bset! { Variant::named(0, vname!("a")) }).unwrap(),
+        );
+        let b = Ty::<SemId>::enumerate(
+            EnumVariants::try_from(bset! { Variant::named(0, vname!("b")) }).unwrap(),
+        );
+        assert!(a.equivalent(&b));
+        assert!(!a.same_shape(&b));
+    }
+
+    #[test]
+    fn normalize() {
+        let newtype = |ty: Ty<TranspileRef>| {
+            Ty::tuple(UnnamedFields::try_from(vec![TranspileRef::from(ty)]).unwrap())
+        };
+        let wrapped = newtype(newtype(Ty::U8));
+        assert_eq!(wrapped.normalize(), Ty::U8);
+        assert_eq!(Ty::array(TranspileRef::from(Ty::U16), 1).normalize(), Ty::U16);
+
+        let named =
+            Ty::tuple(UnnamedFields::try_from(vec![TranspileRef::Named(tn!("Foo"))]).unwrap());
+        assert_eq!(named.normalize(), named);
+        let option = Ty::option(TranspileRef::from(Ty::U8));
+        assert_eq!(option.normalize(), option);
+
+        // Nested inline wrappers are not collapsed
+        let struc = Ty::struc(
+            NamedFields::try_from(vec![
+                field("a", TranspileRef::from(newtype(Ty::U8))),
+                field("b", TranspileRef::from(Ty::U16)),
+            ])
+            .unwrap(),
+        );
+        assert_eq!(struc.normalize(), struc);
+        assert_eq!(newtype(newtype(struc.clone())).normalize(), struc);
+        let list = Ty::list(TranspileRef::from(newtype(Ty::U8)), Sizing::U8);
+        assert_eq!(list.normalize(), list);
+    }
+
+    #[test]
+    fn byte_sequences() {
+        let array = Ty::<SemId>::Array(SemId::byte(), 32);
+        let string = Ty::<SemId>::List(SemId::byte(), Sizing::U8);
+        assert_eq!(array.as_byte_array(), Some(32));
+        assert_eq!(array.as_byte_string(), None);
+        assert_eq!(string.as_byte_string(), Some(Sizing::U8));
+        assert_eq!(string.as_byte_array(), None);
+
+        let chars = Ty::<SemId>::List(SemId::unicode_char(), Sizing::U8);
+        assert_eq!(chars.as_byte_string(), None);
+        assert_eq!(Ty::<SemId>::Array(SemId::unit(), 32).as_byte_array(), None);
+    }
+}
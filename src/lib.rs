@@ -64,7 +64,10 @@ pub use typelib::{
     TranspileRef, TypeLib, TypeLibId,
 };
 pub use typesys::{SymbolicSys, SystemBuilder, TypeSymbol, TypeSysId, TypeSystem};
-pub use util::{parse_args, BuildFragment, PreFragment, SemVer, StlFormat, UnknownFormat, Urn};
+pub use util::{
+    parse_args, BuildFragment, IdentExt, PreFragment, PrimitiveExt, SemVer, SizingError, SizingExt,
+    StlFormat, UnknownFormat, UnknownPrimitive, Urn, VersionOverflow,
+};
 pub use value::{decode, ston, typify, KeyStep, Path, PathError, Step, StrictVal};
 
 pub trait CommitConsume {
@@ -77,3 +80,14 @@ impl CommitConsume for sha2::Sha256 {
         self.update(data)
     }
 }
+
+/// Constructs hasher for computing ids of a specific kind, separating them by committing to the
+/// hash of the kind-specific `tag` twice.
+pub(crate) fn tagged_hasher(tag: [u8; 32]) -> sha2::Sha256 {
+    use sha2::Digest;
+    let tag = sha2::Sha256::new_with_prefix(tag).finalize();
+    let mut hasher = sha2::Sha256::new();
+    hasher.commit_consume(tag);
+    hasher.commit_consume(tag);
+    hasher
+}
@@ -95,3 +95,12 @@ macro_rules! variants {
         }
     };
 }
+
+/// Registers multiple types in [`LibBuilder`](crate::LibBuilder), expanding into a chain of
+/// `.transpile::<T>()` calls and returning the builder.
+#[macro_export]
+macro_rules! transpile_all {
+    ($builder:expr, $($ty:ty),+ $(,)?) => {
+        $builder$(.transpile::<$ty>())+
+    };
+}
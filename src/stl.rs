@@ -41,31 +41,33 @@ pub const LIB_ID_STRICT_TYPES: &str =
     "stl:ReqjX9v2-45ABOvH-i7YYKfx-30V2lgT-owwpkNk-E$v5ENk#century-comrade-chess";
 
 fn _std_sym() -> Result<SymbolicLib, TranspileError> {
-    LibBuilder::new(libname!(LIB_NAME_STD), None)
-        .transpile::<Bool>()
-        .transpile::<U1>()
-        .transpile::<U2>()
-        .transpile::<U3>()
-        .transpile::<U4>()
-        .transpile::<U5>()
-        .transpile::<U6>()
-        .transpile::<U7>()
-        .transpile::<AsciiSym>()
-        .transpile::<AsciiPrintable>()
-        .transpile::<Alpha>()
-        .transpile::<AlphaLodash>()
-        .transpile::<AlphaCaps>()
-        .transpile::<AlphaCapsLodash>()
-        .transpile::<AlphaSmall>()
-        .transpile::<AlphaSmallLodash>()
-        .transpile::<Dec>()
-        .transpile::<HexDecCaps>()
-        .transpile::<HexDecSmall>()
-        .transpile::<AlphaNum>()
-        .transpile::<AlphaCapsNum>()
-        .transpile::<AlphaNumDash>()
-        .transpile::<AlphaNumLodash>()
-        .compile_symbols()
+    transpile_all!(
+        LibBuilder::new(libname!(LIB_NAME_STD), None),
+        Bool,
+        U1,
+        U2,
+        U3,
+        U4,
+        U5,
+        U6,
+        U7,
+        AsciiSym,
+        AsciiPrintable,
+        Alpha,
+        AlphaLodash,
+        AlphaCaps,
+        AlphaCapsLodash,
+        AlphaSmall,
+        AlphaSmallLodash,
+        Dec,
+        HexDecCaps,
+        HexDecSmall,
+        AlphaNum,
+        AlphaCapsNum,
+        AlphaNumDash,
+        AlphaNumLodash,
+    )
+    .compile_symbols()
 }
 
 fn _std_stl() -> Result<TypeLib, CompileError> { _std_sym()?.compile() }
@@ -75,19 +77,21 @@ pub fn std_sym() -> SymbolicLib { _std_sym().expect("invalid strict type Std lib
 pub fn std_stl() -> TypeLib { _std_stl().expect("invalid strict type Std library") }
 
 fn _strict_types_sym() -> Result<SymbolicLib, TranspileError> {
-    LibBuilder::new(libname!(STRICT_TYPES_LIB), [std_stl().to_dependency()])
-        .transpile::<Ident>()
-        .transpile::<TypeName>()
-        .transpile::<FieldName>()
-        .transpile::<VariantName>()
-        .transpile::<LibName>()
-        .transpile::<SymbolRef>()
-        .transpile::<TypeLib>()
-        .transpile::<TypeSysId>()
-        .transpile::<TypeSymbol>()
-        .transpile::<SymbolicSys>()
-        .transpile::<MemoryLayout>()
-        .compile_symbols()
+    transpile_all!(
+        LibBuilder::new(libname!(STRICT_TYPES_LIB), [std_stl().to_dependency()]),
+        Ident,
+        TypeName,
+        FieldName,
+        VariantName,
+        LibName,
+        SymbolRef,
+        TypeLib,
+        TypeSysId,
+        TypeSymbol,
+        SymbolicSys,
+        MemoryLayout,
+    )
+    .compile_symbols()
 }
 fn _strict_types_stl() -> Result<TypeLib, CompileError> { _strict_types_sym()?.compile() }
 
@@ -102,16 +106,13 @@ pub fn strict_types_stl() -> TypeLib {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::typelib::assert_stable_transpile;
 
     #[test]
-    fn std_lib_id() {
-        let lib = std_stl();
-        assert_eq!(lib.id().to_string(), LIB_ID_STD);
-    }
+    fn std_lib_id() { assert_stable_transpile(std_stl).assert_id(LIB_ID_STD); }
 
     #[test]
     fn strict_types_lib_id() {
-        let lib = strict_types_stl();
-        assert_eq!(lib.id().to_string(), LIB_ID_STRICT_TYPES);
+        assert_stable_transpile(strict_types_stl).assert_id(LIB_ID_STRICT_TYPES);
     }
 }
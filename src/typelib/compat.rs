@@ -0,0 +1,343 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of wire compatibility between versions of a type library.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use strict_encoding::{Sizing, TypeName};
+
+use crate::typelib::type_lib::len_prefix;
+use crate::typelib::LibSubref;
+use crate::{LibRef, SemId, Ty, TypeLib};
+
+/// Single reason why data encoded with an older type can't be decoded with its newer version.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum Incompatibility {
+    /// type at `{path}` is not known or can't be resolved.
+    Unresolved { path: String },
+
+    /// type at `{path}` was changed from `{old}` to `{new}`.
+    TypeChanged {
+        path: String,
+        old: String,
+        new: String,
+    },
+
+    /// number of fields at `{path}` was changed from {old} to {new}.
+    FieldCountChanged { path: String, old: u8, new: u8 },
+
+    /// variant with tag {tag} was removed from `{path}`.
+    VariantRemoved { path: String, tag: u8 },
+
+    /// length of the array at `{path}` was changed from {old} to {new}.
+    ArrayLenChanged { path: String, old: u16, new: u16 },
+
+    /// collection size limits at `{path}` were changed from {old} to incompatible {new}.
+    SizingIncompatible {
+        path: String,
+        old: Sizing,
+        new: Sizing,
+    },
+}
+
+/// Result of [`TypeLib::is_wire_compatible`] check.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CompatResult {
+    /// All data encoded with the older type can be decoded with the newer one.
+    Compatible,
+    /// List of changes breaking decoding of the data encoded with the older type.
+    Incompatible(Vec<Incompatibility>),
+}
+
+impl CompatResult {
+    pub fn is_compatible(&self) -> bool { matches!(self, CompatResult::Compatible) }
+}
+
+impl TypeLib {
+    /// Checks whether data encoded using `root` type of this library can be decoded using the
+    /// type with the same name from the `newer` version of the library.
+    ///
+    /// Field and variant names are not part of the encoding and may change freely, as well as
+    /// new variants can be added to enums and unions, and collection size limits may be widened
+    /// as long as this doesn't change the size of the collection length prefix. Since strict
+    /// encoding has no notion of field presence, adding or removing fields (including optional
+    /// ones) always breaks the compatibility.
+    ///
+    /// Types from the library dependencies are compared by their semantic ids only.
+    pub fn is_wire_compatible(&self, newer: &TypeLib, root: &TypeName) -> CompatResult {
+        let mut checker = CompatChecker {
+            old_index: type_index(self),
+            new_index: type_index(newer),
+            visited: empty!(),
+            issues: empty!(),
+        };
+        let mut path = vec![root.to_string()];
+        match (self.types.get(root), newer.types.get(root)) {
+            (Some(old), Some(new)) => checker.check_ty(old, new, &mut path),
+            _ => checker.issues.push(Incompatibility::Unresolved {
+                path: root.to_string(),
+            }),
+        }
+        if checker.issues.is_empty() {
+            CompatResult::Compatible
+        } else {
+            CompatResult::Incompatible(checker.issues)
+        }
+    }
+}
+
+fn type_index(lib: &TypeLib) -> BTreeMap<SemId, &Ty<LibRef>> {
+    lib.types.iter().map(|(name, ty)| (ty.sem_id_named(name), ty)).collect()
+}
+
+struct CompatChecker<'lib> {
+    old_index: BTreeMap<SemId, &'lib Ty<LibRef>>,
+    new_index: BTreeMap<SemId, &'lib Ty<LibRef>>,
+    visited: BTreeSet<(SemId, SemId)>,
+    issues: Vec<Incompatibility>,
+}
+
+impl<'lib> CompatChecker<'lib> {
+    fn unresolved(&mut self, path: &[String]) {
+        self.issues.push(Incompatibility::Unresolved {
+            path: path.join("."),
+        });
+    }
+
+    fn check_ref<A: LibSubref, B: LibSubref>(&mut self, old: &A, new: &B, path: &mut Vec<String>) {
+        let old_id = old.referenced_id();
+        let new_id = new.referenced_id();
+        if let (Some(old_id), Some(new_id)) = (old_id, new_id) {
+            if old_id == new_id || !self.visited.insert((old_id, new_id)) {
+                return;
+            }
+        }
+        let old_named = old.as_sem_id().and_then(|id| self.old_index.get(&id).copied());
+        let new_named = new.as_sem_id().and_then(|id| self.new_index.get(&id).copied());
+        match (old.as_inline(), new.as_inline()) {
+            (Some(old), Some(new)) => self.check_ty(old, new, path),
+            (Some(old), None) => match new_named {
+                Some(new) => self.check_ty(old, new, path),
+                None => self.unresolved(path),
+            },
+            (None, Some(new)) => match old_named {
+                Some(old) => self.check_ty(old, new, path),
+                None => self.unresolved(path),
+            },
+            (None, None) => match (old_named, new_named) {
+                (Some(old), Some(new)) => self.check_ty(old, new, path),
+                _ => self.unresolved(path),
+            },
+        }
+    }
+
+    fn check_item<A: LibSubref, B: LibSubref>(
+        &mut self,
+        old: &A,
+        new: &B,
+        step: String,
+        path: &mut Vec<String>,
+    ) {
+        path.push(step);
+        self.check_ref(old, new, path);
+        path.pop();
+    }
+
+    fn check_sizing(&mut self, old: Sizing, new: Sizing, path: &[String]) {
+        if len_prefix(old) != len_prefix(new) || new.min > old.min || new.max < old.max {
+            self.issues.push(Incompatibility::SizingIncompatible {
+                path: path.join("."),
+                old,
+                new,
+            });
+        }
+    }
+
+    fn check_ty<A: LibSubref, B: LibSubref>(
+        &mut self,
+        old: &Ty<A>,
+        new: &Ty<B>,
+        path: &mut Vec<String>,
+    ) {
+        match (old, new) {
+            (Ty::Primitive(o), Ty::Primitive(n)) if o == n => {}
+            (Ty::UnicodeChar, Ty::UnicodeChar) => {}
+            (Ty::Enum(o), Ty::Enum(n)) => {
+                for variant in o.iter().filter(|variant| !n.has_tag(variant.tag)) {
+                    self.issues.push(Incompatibility::VariantRemoved {
+                        path: path.join("."),
+                        tag: variant.tag,
+                    });
+                }
+            }
+            (Ty::Union(o), Ty::Union(n)) => {
+                for (variant, old_ty) in o {
+                    match n.ty_by_tag(variant.tag) {
+                        Some(new_ty) => {
+                            self.check_item(old_ty, new_ty, variant.name.to_string(), path)
+                        }
+                        None => self.issues.push(Incompatibility::VariantRemoved {
+                            path: path.join("."),
+                            tag: variant.tag,
+                        }),
+                    }
+                }
+            }
+            (Ty::Struct(_) | Ty::Tuple(_), Ty::Struct(_) | Ty::Tuple(_)) => {
+                let (o, n) = (old.count_type_refs(), new.count_type_refs());
+                if o != n {
+                    self.issues.push(Incompatibility::FieldCountChanged {
+                        path: path.join("."),
+                        old: o,
+                        new: n,
+                    });
+                    return;
+                }
+                for pos in 0..o {
+                    let step = old.case_at(pos).map(|case| case.to_string());
+                    let (Some(old_ty), Some(new_ty)) = (old.ty_at(pos), new.ty_at(pos)) else {
+                        unreachable!("field count is checked")
+                    };
+                    self.check_item(old_ty, new_ty, step.unwrap_or_else(|| pos.to_string()), path);
+                }
+            }
+            (Ty::Array(o, old_len), Ty::Array(n, new_len)) => {
+                if old_len != new_len {
+                    self.issues.push(Incompatibility::ArrayLenChanged {
+                        path: path.join("."),
+                        old: *old_len,
+                        new: *new_len,
+                    });
+                    return;
+                }
+                self.check_item(o, n, s!("#"), path);
+            }
+            (Ty::List(o, old_sizing), Ty::List(n, new_sizing))
+            | (Ty::Set(o, old_sizing), Ty::Set(n, new_sizing)) => {
+                self.check_sizing(*old_sizing, *new_sizing, path);
+                self.check_item(o, n, s!("[]"), path);
+            }
+            (Ty::Map(ok, ov, old_sizing), Ty::Map(nk, nv, new_sizing)) => {
+                self.check_sizing(*old_sizing, *new_sizing, path);
+                self.check_item(ok, nk, s!("[key]"), path);
+                self.check_item(ov, nv, s!("[value]"), path);
+            }
+            (old, new) => self.issues.push(Incompatibility::TypeChanged {
+                path: path.join("."),
+                old: old.to_string(),
+                new: new.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(dead_code)]
+
+    use amplify::confinement::Confined;
+
+    use super::*;
+    use crate::stl::std_stl;
+    use crate::LibBuilder;
+
+    macro_rules! order_version {
+        ($ver:ident, $id:ty, $max:literal, $($variant:ident = $tag:literal),+) => {
+            mod $ver {
+                use super::*;
+
+                #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+                #[derive(StrictType, StrictEncode, StrictDecode)]
+                #[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+                #[repr(u8)]
+                pub enum Kind {
+                    #[default]
+                    $($variant = $tag),+
+                }
+
+                #[derive(Clone, Eq, PartialEq, Debug, Default)]
+                #[derive(StrictType, StrictEncode, StrictDecode)]
+                #[strict_type(lib = "TestLib")]
+                pub struct Order {
+                    pub id: $id,
+                    pub kind: Kind,
+                    pub note: Confined<Vec<u8>, 0, $max>,
+                }
+
+                pub fn lib() -> TypeLib {
+                    LibBuilder::new("TestLib", [std_stl().to_dependency()])
+                        .transpile::<Order>()
+                        .compile()
+                        .unwrap()
+                }
+            }
+        };
+    }
+
+    order_version!(v1, u64, 100, Buy = 0, Sell = 1);
+    order_version!(v2, u64, 200, Buy = 0, Sell = 1, Swap = 2);
+    order_version!(v3, u32, 300, Buy = 0);
+
+    #[test]
+    fn wire_compatibility() {
+        let (v1, v2, v3) = (v1::lib(), v2::lib(), v3::lib());
+        let root = tn!("Order");
+        assert_eq!(v1.is_wire_compatible(&v1, &root), CompatResult::Compatible);
+        assert_eq!(v1.is_wire_compatible(&v2, &root), CompatResult::Compatible);
+        assert_eq!(
+            v2.is_wire_compatible(&v1, &root),
+            CompatResult::Incompatible(vec![
+                Incompatibility::VariantRemoved {
+                    path: s!("Order.kind"),
+                    tag: 2
+                },
+                Incompatibility::SizingIncompatible {
+                    path: s!("Order.note"),
+                    old: Sizing::new(0, 200),
+                    new: Sizing::new(0, 100),
+                },
+            ])
+        );
+        assert_eq!(
+            v1.is_wire_compatible(&v3, &root),
+            CompatResult::Incompatible(vec![
+                Incompatibility::TypeChanged {
+                    path: s!("Order.id"),
+                    old: s!("U64"),
+                    new: s!("U32"),
+                },
+                Incompatibility::VariantRemoved {
+                    path: s!("Order.kind"),
+                    tag: 1
+                },
+                Incompatibility::SizingIncompatible {
+                    path: s!("Order.note"),
+                    old: Sizing::new(0, 100),
+                    new: Sizing::new(0, 300),
+                },
+            ])
+        );
+        assert!(!v1.is_wire_compatible(&v1, &tn!("Unknown")).is_compatible());
+    }
+}
@@ -23,10 +23,11 @@
 use std::collections::BTreeMap;
 
 use encoding::LibName;
-use strict_encoding::TypeName;
+use strict_encoding::{FieldName, TypeName};
 
+use crate::ast::Path;
 use crate::typelib::{Dependency, ExternTypes, InlineRef, InlineRef1, InlineRef2, LibRef};
-use crate::{SemId, Translate, TranspileError, TranspileRef, Ty};
+use crate::{SemId, Translate, TranspileError, TranspileRef};
 
 pub type TypeIndex = BTreeMap<TypeName, SemId>;
 
@@ -39,18 +40,25 @@ pub enum CompileError {
     /// a different type with name `{0}` is already present
     DuplicateName(TypeName),
 
-    /// type `{unknown}` referenced inside `{within}` is not known
+    /// type `{unknown}` referenced inside `{within}{path}` is not known
     UnknownType {
         unknown: TypeName,
-        within: Ty<TranspileRef>,
+        within: TypeName,
+        path: Path,
     },
 
+    /// type `{within}` references unknown type {unknown}.
+    DanglingRef { within: TypeName, unknown: SemId },
+
     /// return type indicating continue operation
     Continue,
 
     /// dependency {0} is already present in the library
     DuplicatedDependency(Dependency),
 
+    /// type `{0}` differs from the type with the same name in the imported library.
+    ImportMismatch(TypeName),
+
     /// too deep type nesting for type {2} inside {0}, path {1}
     NestedInline(TypeName, String, String),
 
@@ -65,18 +73,55 @@ pub enum CompileError {
 
     /// library `{0}` contains too many types.
     LibTooLarge(LibName),
+
+    /// type `{ty}{path}` contains several fields or variants named `{name}`.
+    DuplicateFieldName {
+        ty: TypeName,
+        path: Path,
+        name: FieldName,
+    },
+
+    /// floating-point number found at `{path}` while floats are forbidden in the library.
+    FloatForbidden { path: String },
+
+    /// type `{0}` is neither transpiled explicitly nor used by the explicitly transpiled types.
+    UnusedType(TypeName),
+
+    /// type `{name}` contains no data, which happens when a generic type is transpiled with
+    /// placeholder parameters like `()`.
+    DegenerateType { name: TypeName },
+
+    /// Rust type `{type_name}` can't be represented as a strict type: {reason}.
+    Unsupported {
+        type_name: String,
+        reason: &'static str,
+    },
 }
 
 impl From<TranspileError> for CompileError {
     fn from(err: TranspileError) -> Self {
         match err {
-            TranspileError::UnknownType { unknown, within } => {
-                Self::UnknownType { unknown, within }
-            }
+            TranspileError::UnknownType {
+                unknown,
+                within,
+                path,
+            } => Self::UnknownType {
+                unknown,
+                within,
+                path,
+            },
             TranspileError::UnknownLib(lib) => Self::UnknownLib(lib),
             TranspileError::TooManyDependencies => Self::TooManyDependencies,
             TranspileError::TooManyTypes => Self::TooManyTypes,
             TranspileError::LibTooLarge(lib) => Self::LibTooLarge(lib),
+            TranspileError::AliasCollision(name) | TranspileError::NameCollision(name) => {
+                Self::DuplicateName(name)
+            }
+            TranspileError::ImportMismatch(name) => Self::ImportMismatch(name),
+            TranspileError::DegenerateType { name } => Self::DegenerateType { name },
+            TranspileError::Unsupported { type_name, reason } => {
+                Self::Unsupported { type_name, reason }
+            }
         }
     }
 }
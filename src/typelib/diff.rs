@@ -0,0 +1,288 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+
+use strict_encoding::{LibName, TypeName};
+
+use crate::typelib::TypeLibId;
+use crate::typesys::{SymTy, TypeFqn};
+use crate::{SemId, SystemBuilder, Ty, TypeLib};
+
+/// Libraries providing the types referenced by a library, indexed by their ids.
+pub type DepSet = BTreeMap<TypeLibId, TypeLib>;
+
+/// Differences between two versions of a library, reported by [`TypeLib::deep_diff`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TypeLibDiff {
+    /// Types present only in the newer version.
+    pub added: BTreeSet<TypeName>,
+    /// Types present only in the older version.
+    pub removed: BTreeSet<TypeName>,
+    /// Types which definitions have changed, either directly or in the other types of the same
+    /// library they reference.
+    pub changed: BTreeSet<TypeName>,
+    /// Types which are defined in the same way, but reference types from the dependencies which
+    /// have changed, listed for each of the types.
+    pub dep_changed: BTreeMap<TypeName, BTreeSet<TypeFqn>>,
+    /// Referenced types which can't be found in the provided dependencies. The types referencing
+    /// them are reported in [`TypeLibDiff::dep_changed`], unless they have local changes.
+    pub unresolved: BTreeSet<SemId>,
+}
+
+impl TypeLibDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.dep_changed.is_empty()
+    }
+}
+
+impl Display for TypeLibDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for name in &self.added {
+            writeln!(f, "+ {name}")?;
+        }
+        for name in &self.removed {
+            writeln!(f, "- {name}")?;
+        }
+        for name in &self.changed {
+            writeln!(f, "~ {name}")?;
+        }
+        for (name, deps) in &self.dep_changed {
+            write!(f, "~ {name} via")?;
+            for (no, fqn) in deps.iter().enumerate() {
+                let sep = if no == 0 { " " } else { ", " };
+                write!(f, "{sep}{fqn}")?;
+            }
+            writeln!(f)?;
+        }
+        for sem_id in &self.unresolved {
+            writeln!(f, "? {sem_id}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Library types together with all the types from its dependencies, indexed by semantic ids.
+struct Resolved {
+    name: LibName,
+    types: BTreeMap<SemId, SymTy>,
+}
+
+impl Resolved {
+    fn with(lib: &TypeLib, deps: &DepSet) -> Self {
+        let mut builder = SystemBuilder::new();
+        for dep in deps.values().chain([lib]) {
+            builder = builder.import(dep.clone()).expect("library import is infallible");
+        }
+        Resolved {
+            name: lib.name.clone(),
+            types: builder.into_types(),
+        }
+    }
+
+    /// Returns name of the type if it comes from a dependency.
+    fn dep_fqn(&self, ty: &SymTy) -> Option<TypeFqn> {
+        ty.orig.as_ref().filter(|fqn| fqn.lib != self.name).cloned()
+    }
+}
+
+/// Walks two versions of a type in parallel, looking for the origin of the changes.
+struct DiffWalker<'a> {
+    older: &'a Resolved,
+    newer: &'a Resolved,
+    visited: BTreeSet<(SemId, SemId)>,
+    local: bool,
+    deps: BTreeSet<TypeFqn>,
+    unresolved: BTreeSet<SemId>,
+}
+
+impl DiffWalker<'_> {
+    fn walk(&mut self, older: SemId, newer: SemId) {
+        if older == newer || !self.visited.insert((older, newer)) {
+            return;
+        }
+        let (Some(a), Some(b)) = (self.older.types.get(&older), self.newer.types.get(&newer))
+        else {
+            if !self.older.types.contains_key(&older) {
+                self.unresolved.insert(older);
+            }
+            if !self.newer.types.contains_key(&newer) {
+                self.unresolved.insert(newer);
+            }
+            return;
+        };
+        let (dep_a, dep_b) = (self.older.dep_fqn(a), self.newer.dep_fqn(b));
+        if dep_a.is_some() || dep_b.is_some() {
+            self.deps.extend(dep_a.into_iter().chain(dep_b));
+            return;
+        }
+        let renamed = a.orig.as_ref().map(|fqn| &fqn.name) != b.orig.as_ref().map(|fqn| &fqn.name);
+        if renamed || !same_layout(&a.ty, &b.ty) {
+            self.local = true;
+            return;
+        }
+        for ((a, _), (b, _)) in a.ty.type_refs().zip(b.ty.type_refs()) {
+            self.walk(*a, *b);
+        }
+    }
+}
+
+/// Compares types ignoring the types they reference.
+fn same_layout(a: &Ty<SemId>, b: &Ty<SemId>) -> bool {
+    match (a, b) {
+        (Ty::Primitive(a), Ty::Primitive(b)) => a == b,
+        (Ty::UnicodeChar, Ty::UnicodeChar) => true,
+        (Ty::Enum(a), Ty::Enum(b)) => a == b,
+        (Ty::Union(a), Ty::Union(b)) => a.keys().eq(b.keys()),
+        (Ty::Struct(a), Ty::Struct(b)) => {
+            a.iter().map(|field| &field.name).eq(b.iter().map(|field| &field.name))
+        }
+        (Ty::Tuple(a), Ty::Tuple(b)) => a.len() == b.len(),
+        (Ty::Array(_, a), Ty::Array(_, b)) => a == b,
+        (Ty::List(_, a), Ty::List(_, b))
+        | (Ty::Set(_, a), Ty::Set(_, b))
+        | (Ty::Map(_, _, a), Ty::Map(_, _, b)) => a == b,
+        _ => false,
+    }
+}
+
+impl TypeLib {
+    /// Compares the library with its `other` version, resolving references to the external
+    /// types with the provided dependencies of each of the versions.
+    ///
+    /// A type is reported as changed if its semantic id differs, which happens if its resolved
+    /// structure is different. The change is attributed to the dependencies if the type and all
+    /// types of the library it references are defined in the same way, such that the difference
+    /// comes from the external types only.
+    pub fn deep_diff(
+        &self,
+        other: &TypeLib,
+        self_deps: &DepSet,
+        other_deps: &DepSet,
+    ) -> TypeLibDiff {
+        let older = Resolved::with(self, self_deps);
+        let newer = Resolved::with(other, other_deps);
+        let mut diff = TypeLibDiff {
+            added: other
+                .types
+                .keys()
+                .filter(|name| !self.types.contains_key(*name))
+                .cloned()
+                .collect(),
+            removed: self
+                .types
+                .keys()
+                .filter(|name| !other.types.contains_key(*name))
+                .cloned()
+                .collect(),
+            ..default!()
+        };
+        for (name, ty) in &self.types {
+            let Some(other_ty) = other.types.get(name) else {
+                continue;
+            };
+            let mut walker = DiffWalker {
+                older: &older,
+                newer: &newer,
+                visited: empty!(),
+                local: false,
+                deps: empty!(),
+                unresolved: empty!(),
+            };
+            let (older_id, newer_id) = (ty.sem_id_named(name), other_ty.sem_id_named(name));
+            if older_id == newer_id {
+                continue;
+            }
+            walker.walk(older_id, newer_id);
+            let external = !walker.deps.is_empty() || !walker.unresolved.is_empty();
+            diff.unresolved.extend(walker.unresolved);
+            if walker.local || !external {
+                diff.changed.insert(name.clone());
+            } else {
+                diff.dep_changed.insert(name.clone(), walker.deps);
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::UnnamedFields;
+    use crate::stl::std_stl;
+    use crate::LibRef;
+
+    #[test]
+    fn deep_diff() {
+        let std1 = std_stl();
+        let mut std2 = std1.clone();
+        let u1 = std2.types.get(&tn!("U1")).unwrap().clone();
+        std2.types.insert(tn!("Bool"), u1).unwrap();
+
+        let lib = |std: &TypeLib, count: Ty<LibRef>| {
+            let bool = std.types.get(&tn!("Bool")).unwrap().sem_id_named(&tn!("Bool"));
+            let flag = Ty::<LibRef>::tuple(
+                UnnamedFields::try_from(vec![LibRef::external(std.id(), bool)]).unwrap(),
+            );
+            let pair = Ty::tuple(
+                UnnamedFields::try_from(vec![
+                    LibRef::Named(flag.sem_id_named(&tn!("Flag"))),
+                    LibRef::Inline(Ty::U8),
+                ])
+                .unwrap(),
+            );
+            let mut lib = TypeLib::single(libname!("Test"), tn!("Flag"), flag).unwrap();
+            lib.import(std.to_dependency()).unwrap();
+            lib.populate(tn!("Pair"), pair).unwrap();
+            lib.populate(tn!("Count"), count).unwrap();
+            lib
+        };
+        let older = lib(&std1, Ty::U8);
+        let deps1 = bmap! { std1.id() => std1.clone() };
+        let deps2 = bmap! { std2.id() => std2.clone() };
+
+        assert!(older.deep_diff(&older, &deps1, &deps1).is_empty());
+
+        let mut newer = lib(&std2, Ty::U16);
+        newer.populate(tn!("Extra"), Ty::U8).unwrap();
+        let diff = older.deep_diff(&newer, &deps1, &deps2);
+        assert_eq!(diff.added, bset! { tn!("Extra") });
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed, bset! { tn!("Count") });
+        let via = bset! { TypeFqn::with(libname!("Std"), tn!("Bool")) };
+        assert_eq!(diff.dep_changed, bmap! { tn!("Flag") => via.clone(), tn!("Pair") => via });
+        assert!(diff.unresolved.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "+ Extra\n~ Count\n~ Flag via Std.Bool\n~ Pair via Std.Bool\n"
+        );
+
+        let diff = older.deep_diff(&newer, &deps1, &none!());
+        assert_eq!(diff.dep_changed.len(), 2);
+        assert_eq!(diff.unresolved.len(), 1);
+    }
+}
@@ -0,0 +1,779 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of type libraries into schema languages of other ecosystems.
+
+use std::collections::BTreeMap;
+
+use strict_encoding::{LibName, TypeName};
+
+use crate::typelib::{LibSubref, UnknownTypeName};
+use crate::{SemId, TypeLib};
+
+/// Errors exporting type library into a foreign schema language.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ExportError {
+    /// {0}
+    #[from]
+    UnknownType(UnknownTypeName),
+
+    /// type {0} is referenced by the library, but is neither defined in it nor listed among its
+    /// external types.
+    UnresolvedRef(SemId),
+
+    /// invalid Protobuf package name `{0}`.
+    InvalidPackage(String),
+
+    /// enum `{location}` has several variants mapping to the same Protobuf value name `{value}`.
+    DuplicateValue { location: String, value: String },
+
+    /// `{location}` can't be exported: {reason}.
+    Unsupported {
+        location: String,
+        reason: &'static str,
+    },
+}
+
+/// Resolves semantic ids of the named types referenced from a library into their names.
+struct Exporter {
+    names: BTreeMap<SemId, TypeName>,
+    externs: BTreeMap<SemId, (LibName, TypeName)>,
+}
+
+impl Exporter {
+    fn with(lib: &TypeLib) -> Self {
+        let names =
+            lib.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name.clone())).collect();
+        let externs = lib
+            .extern_types
+            .iter()
+            .flat_map(|(lib_name, types)| {
+                types.iter().map(move |(sem_id, name)| (*sem_id, (lib_name.clone(), name.clone())))
+            })
+            .collect();
+        Exporter { names, externs }
+    }
+
+    fn local(&self, r: &impl LibSubref) -> Option<Result<&TypeName, ExportError>> {
+        let sem_id = r.as_sem_id()?;
+        Some(self.names.get(&sem_id).ok_or(ExportError::UnresolvedRef(sem_id)))
+    }
+
+    fn external(&self, r: &impl LibSubref) -> Option<Result<&(LibName, TypeName), ExportError>> {
+        let sem_id = r.as_extern()?.sem_id;
+        Some(self.externs.get(&sem_id).ok_or(ExportError::UnresolvedRef(sem_id)))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod json {
+    use serde_json::{json, Map, Value};
+    use strict_encoding::{NumCls, Primitive, TypeName};
+
+    use super::{ExportError, Exporter};
+    use crate::typelib::LibSubref;
+    use crate::{Ty, TypeLib};
+
+    /// JSON Schema dialect of the documents produced by [`TypeLib::to_json_schema`].
+    pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+    impl TypeLib {
+        /// Exports `root` type and all library types it references as a JSON Schema document
+        /// (draft 2020-12), describing the JSON representation of the strict values.
+        ///
+        /// Each library type becomes an entry in `$defs`, referenced with `$ref`; inline types
+        /// are expanded in place. Types from the dependencies are referenced as
+        /// `<LibName>.json#/$defs/<TypeName>`, i.e. the schema of the dependency must be
+        /// exported with those types reachable from its root.
+        ///
+        /// The mapping is lossy:
+        /// - integer primitives are `integer`s with their value range; for integers wider than 64
+        ///   bits only the lower bound is given, since JSON numbers can't carry such values
+        ///   exactly;
+        /// - float primitives are unbounded `number`s;
+        /// - unit is `null`, unicode character is a single-character `string`;
+        /// - enums are `enum`s of the variant names;
+        /// - unions are `oneOf` a variant name `const` (for variants without data) or an object
+        ///   with a single property named after the variant;
+        /// - tuples are arrays with `prefixItems`, structs - objects with all fields `required`;
+        /// - arrays, lists and sets are `array`s with `minItems`/`maxItems` from their sizing (sets
+        ///   are also `uniqueItems`), except lists of unicode characters, which are `string`s with
+        ///   `minLength`/`maxLength`;
+        /// - maps are `object`s with `minProperties`/`maxProperties`; key type is not represented,
+        ///   since JSON object keys are always strings.
+        ///
+        /// Returned value is canonical: the same library always produces the same document.
+        pub fn to_json_schema(&self, root: &TypeName) -> Result<Value, ExportError> {
+            let exporter = Exporter::with(self);
+            let mut defs = Map::new();
+            for name in self.reachable_from(root)? {
+                let ty = self.types.get(&name).expect("reachable type is present in the library");
+                defs.insert(name.to_string(), exporter.json_ty(ty)?);
+            }
+            Ok(json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "title": self.fqn(root).to_string(),
+                "$ref": format!("#/$defs/{root}"),
+                "$defs": defs,
+            }))
+        }
+    }
+
+    impl Exporter {
+        fn json_ty<Ref: LibSubref>(&self, ty: &Ty<Ref>) -> Result<Value, ExportError> {
+            Ok(match ty {
+                Ty::Primitive(prim) => json_primitive(*prim),
+                Ty::UnicodeChar => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+                Ty::Enum(variants) => {
+                    let names = variants.iter().map(|v| v.name.to_string()).collect::<Vec<_>>();
+                    json!({ "enum": names })
+                }
+                Ty::Union(variants) => {
+                    let mut one_of = vec![];
+                    for (variant, r) in variants.iter() {
+                        let name = variant.name.to_string();
+                        if r.is_unit() {
+                            one_of.push(json!({ "const": name }));
+                        } else {
+                            one_of.push(json!({
+                                "type": "object",
+                                "properties": { name.clone(): self.json_ref(r)? },
+                                "required": [name],
+                                "additionalProperties": false,
+                            }));
+                        }
+                    }
+                    json!({ "oneOf": one_of })
+                }
+                Ty::Tuple(fields) => {
+                    let items =
+                        fields.iter().map(|r| self.json_ref(r)).collect::<Result<Vec<_>, _>>()?;
+                    json!({
+                        "type": "array",
+                        "prefixItems": items,
+                        "items": false,
+                        "minItems": fields.len(),
+                        "maxItems": fields.len(),
+                    })
+                }
+                Ty::Struct(fields) => {
+                    let mut properties = Map::new();
+                    for field in fields {
+                        properties.insert(field.name.to_string(), self.json_ref(&field.ty)?);
+                    }
+                    let required = fields.iter().map(|f| f.name.to_string()).collect::<Vec<_>>();
+                    json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": required,
+                        "additionalProperties": false,
+                    })
+                }
+                Ty::Array(r, len) => json!({
+                    "type": "array",
+                    "items": self.json_ref(r)?,
+                    "minItems": len,
+                    "maxItems": len,
+                }),
+                Ty::List(r, sizing) if r.is_unicode_char() => json!({
+                    "type": "string",
+                    "minLength": sizing.min,
+                    "maxLength": sizing.max,
+                }),
+                Ty::List(r, sizing) => json!({
+                    "type": "array",
+                    "items": self.json_ref(r)?,
+                    "minItems": sizing.min,
+                    "maxItems": sizing.max,
+                }),
+                Ty::Set(r, sizing) => json!({
+                    "type": "array",
+                    "items": self.json_ref(r)?,
+                    "uniqueItems": true,
+                    "minItems": sizing.min,
+                    "maxItems": sizing.max,
+                }),
+                Ty::Map(_, r, sizing) => json!({
+                    "type": "object",
+                    "additionalProperties": self.json_ref(r)?,
+                    "minProperties": sizing.min,
+                    "maxProperties": sizing.max,
+                }),
+            })
+        }
+
+        fn json_ref<Ref: LibSubref>(&self, r: &Ref) -> Result<Value, ExportError> {
+            if let Some(ty) = r.as_inline() {
+                return self.json_ty(ty);
+            }
+            if let Some(name) = self.local(r) {
+                return Ok(json!({ "$ref": format!("#/$defs/{}", name?) }));
+            }
+            let (lib, name) = self.external(r).expect("library reference of unknown kind")?;
+            Ok(json!({ "$ref": format!("{lib}.json#/$defs/{name}") }))
+        }
+    }
+
+    fn json_primitive(prim: Primitive) -> Value {
+        if prim == Primitive::UNIT {
+            return json!({ "type": "null" });
+        }
+        if prim == Primitive::BYTE {
+            return json_primitive(Primitive::U8);
+        }
+        let bits = prim.byte_size() as u32 * 8;
+        match prim.info().ty {
+            NumCls::Float => json!({ "type": "number" }),
+            NumCls::Unsigned | NumCls::NonZero if bits > 64 => {
+                json!({ "type": "integer", "minimum": (prim.info().ty == NumCls::NonZero) as u8 })
+            }
+            NumCls::Signed if bits > 64 => json!({ "type": "integer" }),
+            NumCls::Unsigned => {
+                json!({ "type": "integer", "minimum": 0, "maximum": u64::MAX >> (64 - bits) })
+            }
+            NumCls::NonZero => {
+                json!({ "type": "integer", "minimum": 1, "maximum": u64::MAX >> (64 - bits) })
+            }
+            NumCls::Signed => json!({
+                "type": "integer",
+                "minimum": i64::MIN >> (64 - bits),
+                "maximum": i64::MAX >> (64 - bits),
+            }),
+        }
+    }
+}
+
+mod proto {
+    use std::collections::BTreeSet;
+    use std::fmt::{self, Display, Formatter};
+
+    use strict_encoding::{NumCls, Primitive};
+
+    use super::{ExportError, Exporter};
+    use crate::typelib::LibSubref;
+    use crate::{Ty, TypeLib};
+
+    const EMPTY: &str = "google.protobuf.Empty";
+    const EMPTY_IMPORT: &str = "google/protobuf/empty.proto";
+    const MAP_KEYS: [&str; 6] = ["int32", "int64", "uint32", "uint64", "bool", "string"];
+
+    #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+    enum Label {
+        Single,
+        Optional,
+        Repeated,
+        Map,
+    }
+
+    /// Type of a message field together with its label.
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    struct ProtoField {
+        label: Label,
+        ty: String,
+    }
+
+    impl ProtoField {
+        fn single(ty: impl ToString) -> Self {
+            ProtoField {
+                label: Label::Single,
+                ty: ty.to_string(),
+            }
+        }
+    }
+
+    impl Display for ProtoField {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self.label {
+                Label::Single | Label::Map => f.write_str(&self.ty),
+                Label::Optional => write!(f, "optional {}", self.ty),
+                Label::Repeated => write!(f, "repeated {}", self.ty),
+            }
+        }
+    }
+
+    impl TypeLib {
+        /// Exports all library types as a Protobuf (proto3) definition file with the provided
+        /// package name.
+        ///
+        /// Library types are mapped to the top-level definitions:
+        /// - structs and tuples become messages, with tuple fields named `item0`, `item1` etc;
+        /// - enums become proto enums, with the values named `<PREFIX>_<VARIANT>` by the type and
+        ///   variant names in upper snake case, and numbered by the variant tags. If there is no
+        ///   variant with tag `0`, a `<PREFIX>_UNSPECIFIED = 0` value is added, as required by
+        ///   proto3;
+        /// - unions become messages with a `oneof variant`, numbered by the variant tag plus one;
+        ///   variants without data have `google.protobuf.Empty` type;
+        /// - other types become messages with a single field `value`.
+        ///
+        /// Integer primitives map to the smallest of `uint32`, `uint64`, `int32` and `int64`
+        /// which fits them, floats up to 32 bits - to `float`, and 64-bit floats - to `double`.
+        /// Byte arrays and lists are `bytes`, lists of unicode characters are `string`s, other
+        /// arrays and lists are `repeated` fields, options are `optional` fields and maps are
+        /// `map<K, V>` fields. Array length and collection sizing are not represented. Inline
+        /// single-field tuples are replaced with their field type, other inline compound types
+        /// become nested messages named after the field or variant. Where proto doesn't allow a
+        /// label (inside a `oneof`, a collection or a map value), the field is wrapped into a
+        /// nested message with a single `value` field.
+        ///
+        /// Types from the dependencies are referenced as `<LibName>.<TypeName>` and imported from
+        /// `<LibName>.proto`, i.e. the dependencies must be exported with their library name as
+        /// the package name.
+        ///
+        /// # Errors
+        ///
+        /// Fails with [`ExportError::Unsupported`] if the library uses types which can't be
+        /// represented in Protobuf without loss of data: integers wider than 64 bits, floats
+        /// wider than 64 bits, sets, and maps with keys which are not integers or strings. Fails
+        /// with [`ExportError::DuplicateValue`] if several variants of an enum map to the same
+        /// value name, like `a` and `_A` in `Std.Alpha`.
+        pub fn to_proto(&self, package: &str) -> Result<String, ExportError> {
+            let valid = package.split('.').all(|ident| {
+                ident.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+            if !valid {
+                return Err(ExportError::InvalidPackage(package.to_owned()));
+            }
+
+            let exporter = Exporter::with(self);
+            let mut writer = ProtoWriter {
+                exporter: &exporter,
+                imports: empty!(),
+            };
+            let mut body = String::new();
+            for (name, ty) in &self.types {
+                body.push('\n');
+                writer.message(name.as_str(), ty, &self.fqn(name).to_string(), 0, &mut body)?;
+            }
+
+            let mut s = format!("syntax = \"proto3\";\n\npackage {package};\n");
+            if !writer.imports.is_empty() {
+                s.push('\n');
+            }
+            for import in &writer.imports {
+                s.push_str(&format!("import \"{import}\";\n"));
+            }
+            s.push_str(&body);
+            Ok(s)
+        }
+    }
+
+    struct ProtoWriter<'exp> {
+        exporter: &'exp Exporter,
+        imports: BTreeSet<String>,
+    }
+
+    impl ProtoWriter<'_> {
+        fn message<Ref: LibSubref>(
+            &mut self,
+            name: &str,
+            ty: &Ty<Ref>,
+            location: &str,
+            indent: usize,
+            out: &mut String,
+        ) -> Result<(), ExportError> {
+            let pad = "  ".repeat(indent);
+            if let Ty::Enum(variants) = ty {
+                let prefix = upper_snake(name);
+                out.push_str(&format!("{pad}enum {name} {{\n"));
+                let mut values = BTreeSet::new();
+                let mut add_value = |value: String, tag: u8| {
+                    if !values.insert(value.clone()) {
+                        return Err(ExportError::DuplicateValue {
+                            location: location.to_owned(),
+                            value,
+                        });
+                    }
+                    out.push_str(&format!("{pad}  {value} = {tag};\n"));
+                    Ok(())
+                };
+                if !variants.iter().any(|variant| variant.tag == 0) {
+                    add_value(format!("{prefix}_UNSPECIFIED"), 0)?;
+                }
+                for variant in variants.iter() {
+                    let value = upper_snake(variant.name.as_str());
+                    add_value(format!("{prefix}_{value}"), variant.tag)?;
+                }
+                out.push_str(&format!("{pad}}}\n"));
+                return Ok(());
+            }
+
+            let mut nested = String::new();
+            let mut body = String::new();
+            match ty {
+                Ty::Struct(fields) => {
+                    for (no, field) in fields.iter().enumerate() {
+                        let name = field.name.as_str();
+                        let location = format!("{location}.{name}");
+                        let field =
+                            self.field(name, &field.ty, &location, indent + 1, &mut nested)?;
+                        body.push_str(&format!("{pad}  {field} {name} = {};\n", no + 1));
+                    }
+                }
+                Ty::Tuple(fields) => {
+                    for (no, r) in fields.iter().enumerate() {
+                        let name = format!("item{no}");
+                        let location = format!("{location}.{no}");
+                        let field = self.field(&name, r, &location, indent + 1, &mut nested)?;
+                        body.push_str(&format!("{pad}  {field} {name} = {};\n", no + 1));
+                    }
+                }
+                Ty::Union(variants) => {
+                    body.push_str(&format!("{pad}  oneof variant {{\n"));
+                    for (variant, r) in variants.iter() {
+                        let name = variant.name.as_str();
+                        let field = if r.is_unit() {
+                            self.imports.insert(EMPTY_IMPORT.to_owned());
+                            ProtoField::single(EMPTY)
+                        } else {
+                            let location = format!("{location}.{name}");
+                            let field = self.field(name, r, &location, indent + 1, &mut nested)?;
+                            self.unlabeled(field, name, indent + 1, &mut nested)
+                        };
+                        body.push_str(&format!(
+                            "{pad}    {field} {name} = {};\n",
+                            variant.tag as u16 + 1
+                        ));
+                    }
+                    body.push_str(&format!("{pad}  }}\n"));
+                }
+                _ => {
+                    let field = self.field_ty(ty, "value", location, indent + 1, &mut nested)?;
+                    body.push_str(&format!("{pad}  {field} value = 1;\n"));
+                }
+            }
+            out.push_str(&format!("{pad}message {name} {{\n"));
+            out.push_str(&nested);
+            out.push_str(&body);
+            out.push_str(&format!("{pad}}}\n"));
+            Ok(())
+        }
+
+        fn field<Ref: LibSubref>(
+            &mut self,
+            name: &str,
+            r: &Ref,
+            location: &str,
+            indent: usize,
+            nested: &mut String,
+        ) -> Result<ProtoField, ExportError> {
+            if let Some(ty) = r.as_inline() {
+                return self.field_ty(ty, name, location, indent, nested);
+            }
+            if let Some(ty_name) = self.exporter.local(r) {
+                return Ok(ProtoField::single(ty_name?));
+            }
+            let (lib, ty_name) =
+                self.exporter.external(r).expect("library reference of unknown kind")?;
+            self.imports.insert(format!("{lib}.proto"));
+            Ok(ProtoField::single(format!("{lib}.{ty_name}")))
+        }
+
+        fn field_ty<Ref: LibSubref>(
+            &mut self,
+            ty: &Ty<Ref>,
+            name: &str,
+            location: &str,
+            indent: usize,
+            nested: &mut String,
+        ) -> Result<ProtoField, ExportError> {
+            let unsupported = |reason| ExportError::Unsupported {
+                location: location.to_owned(),
+                reason,
+            };
+            Ok(match ty {
+                Ty::Primitive(prim) if *prim == Primitive::UNIT => {
+                    self.imports.insert(EMPTY_IMPORT.to_owned());
+                    ProtoField::single(EMPTY)
+                }
+                Ty::Primitive(prim) => ProtoField::single(scalar(*prim).map_err(unsupported)?),
+                Ty::UnicodeChar => ProtoField::single("string"),
+                Ty::Array(r, _) | Ty::List(r, _) if r.is_byte() => ProtoField::single("bytes"),
+                Ty::List(r, _) if r.is_unicode_char() => ProtoField::single("string"),
+                Ty::Array(r, _) | Ty::List(r, _) => {
+                    let item = self.field(name, r, location, indent, nested)?;
+                    let item = self.unlabeled(item, &format!("{name}Item"), indent, nested);
+                    ProtoField {
+                        label: Label::Repeated,
+                        ty: item.ty,
+                    }
+                }
+                Ty::Set(..) => return Err(unsupported("Protobuf has no set type")),
+                Ty::Map(key, val, _) => {
+                    let key = self.field(name, key, location, indent, nested)?;
+                    if key.label != Label::Single || !MAP_KEYS.contains(&key.ty.as_str()) {
+                        return Err(unsupported("Protobuf map keys must be integers or strings"));
+                    }
+                    let val = self.field(name, val, location, indent, nested)?;
+                    let val = self.unlabeled(val, &format!("{name}Value"), indent, nested);
+                    ProtoField {
+                        label: Label::Map,
+                        ty: format!("map<{}, {}>", key.ty, val.ty),
+                    }
+                }
+                Ty::Union(_) if ty.is_option() => {
+                    let some = ty.as_some().expect("option type");
+                    let some = self.field(name, some, location, indent, nested)?;
+                    let some = self.unlabeled(some, name, indent, nested);
+                    ProtoField {
+                        label: Label::Optional,
+                        ty: some.ty,
+                    }
+                }
+                Ty::Tuple(fields) if fields.len() == 1 => {
+                    let inner = fields.ty_by_pos(0).expect("newtype field");
+                    self.field(name, inner, location, indent, nested)?
+                }
+                Ty::Enum(_) | Ty::Union(_) | Ty::Tuple(_) | Ty::Struct(_) => {
+                    let nested_name = upper_camel(name);
+                    self.message(&nested_name, ty, location, indent, nested)?;
+                    ProtoField::single(nested_name)
+                }
+            })
+        }
+
+        /// Wraps labeled field into a nested message, which can be used where proto doesn't
+        /// allow labels.
+        fn unlabeled(
+            &mut self,
+            field: ProtoField,
+            name: &str,
+            indent: usize,
+            nested: &mut String,
+        ) -> ProtoField {
+            if field.label == Label::Single {
+                return field;
+            }
+            let pad = "  ".repeat(indent);
+            let name = upper_camel(name);
+            nested
+                .push_str(&format!("{pad}message {name} {{\n{pad}  {field} value = 1;\n{pad}}}\n"));
+            ProtoField::single(name)
+        }
+    }
+
+    fn scalar(prim: Primitive) -> Result<&'static str, &'static str> {
+        if prim == Primitive::BYTE {
+            return Ok("uint32");
+        }
+        let size = prim.byte_size();
+        match prim.info().ty {
+            NumCls::Unsigned | NumCls::NonZero if size <= 4 => Ok("uint32"),
+            NumCls::Unsigned | NumCls::NonZero if size <= 8 => Ok("uint64"),
+            NumCls::Signed if size <= 4 => Ok("int32"),
+            NumCls::Signed if size <= 8 => Ok("int64"),
+            NumCls::Float if size <= 4 => Ok("float"),
+            NumCls::Float if size == 8 => Ok("double"),
+            NumCls::Float => Err("Protobuf has no floating point type wider than 64 bits"),
+            _ => Err("Protobuf has no integer type wider than 64 bits"),
+        }
+    }
+
+    fn upper_camel(name: &str) -> String {
+        let mut chars = name.chars();
+        chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect()
+    }
+
+    /// Converts a camel-case name into upper snake case, collapsing underscores, such that both
+    /// `a` and `_A` become `A`.
+    fn upper_snake(name: &str) -> String {
+        let mut s = String::with_capacity(name.len() + 4);
+        for c in name.chars() {
+            let boundary = c == '_' || c.is_ascii_uppercase();
+            if boundary && !s.is_empty() && !s.ends_with('_') {
+                s.push('_');
+            }
+            if c != '_' {
+                s.push(c.to_ascii_uppercase());
+            }
+        }
+        if s.ends_with('_') {
+            s.pop();
+        }
+        s
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use json::JSON_SCHEMA_DIALECT;
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::{TinyBlob, TinyOrdMap, TinyString, TinyVec};
+
+    use super::*;
+    use crate::LibBuilder;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_schema() {
+        use serde_json::json;
+
+        use crate::stl::strict_types_stl;
+
+        let lib = strict_types_stl();
+        assert_eq!(
+            lib.to_json_schema(&tn!("Sizing")).unwrap(),
+            json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "title": "StrictTypes.Sizing",
+                "$ref": "#/$defs/Sizing",
+                "$defs": {
+                    "Sizing": {
+                        "type": "object",
+                        "properties": {
+                            "min": { "type": "integer", "minimum": 0, "maximum": u64::MAX },
+                            "max": { "type": "integer", "minimum": 0, "maximum": u64::MAX },
+                        },
+                        "required": ["min", "max"],
+                        "additionalProperties": false,
+                    }
+                }
+            })
+        );
+
+        let schema = lib.to_json_schema(&tn!("TypeLib")).unwrap();
+        let defs = schema["$defs"].as_object().unwrap();
+        let reached = lib.reachable_from(&tn!("TypeLib")).unwrap();
+        assert_eq!(defs.len(), reached.len());
+        for name in reached {
+            assert!(defs.contains_key(name.as_str()));
+        }
+
+        assert_eq!(
+            lib.to_json_schema(&tn!("Absent")),
+            Err(ExportError::UnknownType(UnknownTypeName(tn!("Absent"))))
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+    #[repr(u8)]
+    enum Kind {
+        #[default]
+        Small = 1,
+        Large = 2,
+        ExtraLarge = 3,
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib", tags = order)]
+    enum Shape {
+        #[strict_type(dumb)]
+        Point,
+        Circle(u16),
+        Line {
+            from: (u8, u8),
+            to: (u8, u8),
+        },
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Record {
+        kind: Kind,
+        name: TinyString,
+        data: TinyBlob,
+        shapes: TinyVec<Shape>,
+        note: Option<u64>,
+        index: TinyOrdMap<u32, TinyVec<i8>>,
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Wide(u128);
+
+    #[test]
+    fn proto() {
+        let lib =
+            LibBuilder::new(libname!("TestLib"), None).transpile::<Record>().compile().unwrap();
+        assert_eq!(
+            lib.to_proto("test.lib").unwrap(),
+            r#"syntax = "proto3";
+
+package test.lib;
+
+import "google/protobuf/empty.proto";
+
+enum Kind {
+  KIND_UNSPECIFIED = 0;
+  KIND_SMALL = 1;
+  KIND_LARGE = 2;
+  KIND_EXTRA_LARGE = 3;
+}
+
+message Record {
+  message IndexValue {
+    repeated int32 value = 1;
+  }
+  Kind kind = 1;
+  string name = 2;
+  bytes data = 3;
+  repeated Shape shapes = 4;
+  optional uint64 note = 5;
+  map<uint32, IndexValue> index = 6;
+}
+
+message Shape {
+  message Line {
+    message From {
+      uint32 item0 = 1;
+      uint32 item1 = 2;
+    }
+    message To {
+      uint32 item0 = 1;
+      uint32 item1 = 2;
+    }
+    From from = 1;
+    To to = 2;
+  }
+  oneof variant {
+    google.protobuf.Empty point = 1;
+    uint32 circle = 2;
+    Line line = 3;
+  }
+}
+"#
+        );
+        assert_eq!(lib.to_proto("test..lib"), Err(ExportError::InvalidPackage(s!("test..lib"))));
+
+        assert_eq!(
+            crate::stl::std_stl().to_proto("std"),
+            Err(ExportError::DuplicateValue {
+                location: s!("Std.Alpha"),
+                value: s!("ALPHA_A"),
+            })
+        );
+
+        let lib = LibBuilder::new(libname!("TestLib"), None).transpile::<Wide>().compile().unwrap();
+        let err = lib.to_proto("test").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`TestLib.Wide.0` can't be exported: Protobuf has no integer type wider than 64 bits."
+        );
+    }
+}
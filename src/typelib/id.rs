@@ -26,12 +26,12 @@ use std::str::FromStr;
 use amplify::{ByteArray, Bytes32};
 use baid64::{Baid64ParseError, DisplayBaid64, FromBaid64Str};
 use encoding::StrictEncode;
-use sha2::{Digest, Sha256};
+use sha2::Digest;
 use strict_encoding::{StrictDumb, STRICT_TYPES_LIB};
 
 use crate::ast::SemCommit;
 use crate::typelib::{ExternRef, InlineRef, InlineRef1, InlineRef2, TypeLib};
-use crate::{CommitConsume, Dependency, LibRef, SymbolRef, TranspileRef};
+use crate::{tagged_hasher, CommitConsume, Dependency, LibRef, SymbolRef, TranspileRef};
 
 pub const LIB_ID_TAG: [u8; 32] = *b"urn:ubideco:strict-types:lib:v01";
 
@@ -60,6 +60,14 @@ impl DisplayBaid64 for TypeLibId {
     fn to_baid64_payload(&self) -> [u8; 32] { self.to_byte_array() }
 }
 impl FromBaid64Str for TypeLibId {}
+impl TypeLibId {
+    /// Checks whether the mnemonic checksum, like the one following `#` in the alternate display
+    /// form, matches the id. The comparison ignores ASCII case.
+    pub fn verify_mnemonic(&self, mnemonic: &str) -> bool {
+        self.to_baid64_mnemonic().eq_ignore_ascii_case(mnemonic)
+    }
+}
+
 impl FromStr for TypeLibId {
     type Err = Baid64ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> { Self::from_baid64_str(s) }
@@ -151,12 +159,90 @@ impl SemCommit for ExternRef {
 }
 
 impl TypeLib {
+    /// Computes library id.
+    ///
+    /// The id commits to the semantic id of each library type, and each type commits to the
+    /// types it references by their semantic ids, which are stored in the references. Thus each
+    /// type definition is hashed exactly once, and the cost of the computation is linear in the
+    /// size of the library.
     pub fn id(&self) -> TypeLibId {
-        let tag = Sha256::new_with_prefix(LIB_ID_TAG).finalize();
-        let mut hasher = Sha256::new();
-        hasher.commit_consume(tag);
-        hasher.commit_consume(tag);
+        let mut hasher = tagged_hasher(LIB_ID_TAG);
         self.sem_commit(&mut hasher);
         TypeLibId::from_byte_array(hasher.finalize())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use sha2::Sha256;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl, LIB_ID_STRICT_TYPES};
+    use crate::{SemId, Ty};
+
+    #[test]
+    fn lib_id_byte_order() {
+        // Manually constructs the commitment, using explicit little-endian byte order for the
+        // multi-byte type count, independently of the target platform endianness
+        let lib = strict_types_stl();
+        let tag = Sha256::new_with_prefix(LIB_ID_TAG).finalize();
+        let mut preimage = vec![];
+        preimage.extend(tag);
+        preimage.extend(tag);
+        preimage.push(lib.name.len() as u8);
+        preimage.extend(lib.name.as_bytes());
+        preimage.push(lib.dependencies.len() as u8);
+        for dep in &lib.dependencies {
+            preimage.extend(dep.id.to_byte_array());
+        }
+        let count = lib.types.len();
+        preimage.extend([(count & 0xFF) as u8, (count >> 8) as u8]);
+        for (name, ty) in &lib.types {
+            preimage.extend(ty.sem_id_named(name).to_byte_array());
+        }
+        let id = TypeLibId::from_byte_array(Sha256::digest(&preimage));
+        assert_eq!(id, lib.id());
+        assert_eq!(id.to_string(), LIB_ID_STRICT_TYPES);
+    }
+
+    #[test]
+    fn mnemonic() {
+        let id = strict_types_stl().id();
+        let mnemonic = id.to_baid64_mnemonic();
+        assert!(id.verify_mnemonic(&mnemonic));
+        assert!(id.verify_mnemonic(&mnemonic.to_uppercase()));
+        assert!(!id.verify_mnemonic(&mnemonic[1..]));
+        assert!(!std_stl().id().verify_mnemonic(&mnemonic));
+        assert!(!id.verify_mnemonic(""));
+    }
+
+    #[test]
+    fn ref_commitments() {
+        // All nesting levels of library references must commit to the referenced type
+        // identically, such that the semantic ids do not depend on the level at which a type
+        // reference appears
+        let sem_id = SemId::default();
+        let ext = ExternRef::with(std_stl().id(), sem_id);
+        let expected = Ty::<SemId>::Array(sem_id, 4).sem_id_unnamed();
+
+        let named = [
+            Ty::Array(LibRef::Named(sem_id), 4).sem_id_unnamed(),
+            Ty::Array(InlineRef::Named(sem_id), 4).sem_id_unnamed(),
+            Ty::Array(InlineRef1::Named(sem_id), 4).sem_id_unnamed(),
+            Ty::Array(InlineRef2::Named(sem_id), 4).sem_id_unnamed(),
+        ];
+        let external = [
+            Ty::Array(LibRef::Extern(ext.clone()), 4).sem_id_unnamed(),
+            Ty::Array(InlineRef::Extern(ext.clone()), 4).sem_id_unnamed(),
+            Ty::Array(InlineRef1::Extern(ext.clone()), 4).sem_id_unnamed(),
+            Ty::Array(InlineRef2::Extern(ext), 4).sem_id_unnamed(),
+        ];
+        assert!(named.iter().chain(&external).all(|id| *id == expected));
+
+        let inline = Ty::Array(LibRef::Inline(Ty::Array(InlineRef::Named(sem_id), 4)), 1);
+        let inline1 = Ty::Array(InlineRef::Inline(Ty::Array(InlineRef1::Named(sem_id), 4)), 1);
+        let inline2 = Ty::Array(InlineRef1::Inline(Ty::Array(InlineRef2::Named(sem_id), 4)), 1);
+        assert_eq!(inline.sem_id_unnamed(), inline1.sem_id_unnamed());
+        assert_eq!(inline.sem_id_unnamed(), inline2.sem_id_unnamed());
+    }
+}
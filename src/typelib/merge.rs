@@ -0,0 +1,256 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+
+use strict_encoding::TypeName;
+
+use crate::typelib::Dependency;
+use crate::{SemId, TypeLib};
+
+/// Policy for resolving conflicts when merging type libraries, i.e. when both libraries
+/// define a type under the same name but with a different semantic id.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum MergePolicy {
+    /// Fail the merge on the first conflicting type.
+    #[default]
+    Strict,
+    /// Keep the type from the library into which the merge happens.
+    PreferSelf,
+    /// Replace the type with the one from the merged library.
+    PreferOther,
+}
+
+/// Information about the changes made by [`TypeLib::merge_with`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct MergeReport {
+    /// Types which were not present in the library and were added from the other one.
+    pub added: BTreeSet<TypeName>,
+    /// Conflicting types which were replaced with the types from the other library.
+    pub overridden: BTreeSet<TypeName>,
+    /// Conflicting types which were kept as they were in the library.
+    pub kept: BTreeSet<TypeName>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum MergeError {
+    /// both libraries define type `{0}`, but with different semantic ids.
+    Conflict(TypeName),
+
+    /// merged library contains too many types.
+    TooManyTypes,
+
+    /// merged library contains too many dependencies.
+    TooManyDependencies,
+
+    /// dependency {0} conflicts with a dependency of the library having the same name or id.
+    DependencyConflict(Dependency),
+
+    /// after the merge type `{within}` references type {unknown}, which was replaced or not
+    /// taken from the other library.
+    DanglingRef { within: TypeName, unknown: SemId },
+}
+
+impl TypeLib {
+    /// Merges types, dependencies and external types from `other` library, resolving conflicts
+    /// between types with the same name according to the `policy`.
+    ///
+    /// Conflicting types are detected by comparing their semantic ids. If a conflicting type
+    /// resolved with [`MergePolicy::PreferSelf`] or [`MergePolicy::PreferOther`] is referenced
+    /// by other types of the merged library, the merge fails with [`MergeError::DanglingRef`].
+    /// Dependencies are merged only if they don't share a name or an id with a different
+    /// dependency. In case of an error the library is left unmodified.
+    pub fn merge_with(
+        &mut self,
+        other: TypeLib,
+        policy: MergePolicy,
+    ) -> Result<MergeReport, MergeError> {
+        let mut report = MergeReport::default();
+        let mut types = self.types.clone();
+        for (name, ty) in other.types {
+            match types.get(&name) {
+                None => {
+                    report.added.insert(name.clone());
+                }
+                Some(existing) if existing.sem_id_named(&name) == ty.sem_id_named(&name) => {
+                    continue
+                }
+                Some(_) => match policy {
+                    MergePolicy::Strict => return Err(MergeError::Conflict(name)),
+                    MergePolicy::PreferSelf => {
+                        report.kept.insert(name);
+                        continue;
+                    }
+                    MergePolicy::PreferOther => {
+                        report.overridden.insert(name.clone());
+                    }
+                },
+            }
+            types.insert(name, ty).map_err(|_| MergeError::TooManyTypes)?;
+        }
+
+        let mut dependencies = self.dependencies.clone();
+        for dep in other.dependencies {
+            match dependencies.iter().find(|d| d.id == dep.id || d.name == dep.name) {
+                Some(d) if d.id == dep.id && d.name == dep.name => {}
+                Some(_) => return Err(MergeError::DependencyConflict(dep)),
+                None => dependencies.push(dep).map_err(|_| MergeError::TooManyDependencies)?,
+            }
+        }
+
+        let mut extern_types = self.extern_types.clone();
+        for (lib, ext) in other.extern_types {
+            let mut merged = extern_types.get(&lib).cloned().unwrap_or_default();
+            merged.extend(ext).map_err(|_| MergeError::TooManyTypes)?;
+            extern_types.insert(lib, merged).map_err(|_| MergeError::TooManyDependencies)?;
+        }
+
+        let merged = TypeLib {
+            name: self.name.clone(),
+            dependencies,
+            extern_types,
+            types,
+        };
+        if let Err(dangling) = merged.verify_ids() {
+            let (within, unknown) = dangling[0].clone();
+            return Err(MergeError::DanglingRef { within, unknown });
+        }
+        *self = merged;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use strict_encoding::{Sizing, STRICT_TYPES_LIB};
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::typelib::LibRef;
+    use crate::{LibBuilder, Ty};
+
+    fn lib(types: impl IntoIterator<Item = (TypeName, Ty<LibRef>)>) -> TypeLib {
+        TypeLib {
+            name: libname!("Merged"),
+            dependencies: none!(),
+            extern_types: none!(),
+            types: Confined::try_from_iter(types).unwrap(),
+        }
+    }
+
+    #[test]
+    fn merge_policies() {
+        let base = std_stl();
+        let mut overlay = base.clone();
+        let ty = overlay.types.get(&tn!("Bool")).unwrap().clone();
+        overlay.types.insert(tn!("Bit"), ty).unwrap();
+        let u1 = overlay.types.get(&tn!("U1")).unwrap().clone();
+        overlay.types.insert(tn!("Bool"), u1).unwrap();
+
+        let mut lib = base.clone();
+        assert_eq!(
+            lib.merge_with(overlay.clone(), MergePolicy::Strict),
+            Err(MergeError::Conflict(tn!("Bool")))
+        );
+        assert_eq!(lib, base);
+
+        let report = lib.merge_with(overlay.clone(), MergePolicy::PreferSelf).unwrap();
+        assert_eq!(report.added, bset! { tn!("Bit") });
+        assert_eq!(report.kept, bset! { tn!("Bool") });
+        assert_eq!(lib.types.get(&tn!("Bool")), base.types.get(&tn!("Bool")));
+
+        let mut lib = base.clone();
+        let report = lib.merge_with(overlay.clone(), MergePolicy::PreferOther).unwrap();
+        assert_eq!(report.overridden, bset! { tn!("Bool") });
+        assert_eq!(lib.types, overlay.types);
+
+        let ext = LibBuilder::new(libname!(STRICT_TYPES_LIB), [base.to_dependency()])
+            .transpile::<crate::SymbolRef>()
+            .compile()
+            .unwrap();
+        let mut lib = base.clone();
+        lib.merge_with(ext.clone(), MergePolicy::Strict).unwrap();
+        assert_eq!(lib.dependencies, ext.dependencies);
+    }
+
+    #[test]
+    fn merge_dangling() {
+        let inner_id = Ty::<LibRef>::U8.sem_id_named(&tn!("Inner"));
+        let outer = Ty::list(LibRef::named(inner_id), Sizing::ONE);
+        let base = lib([(tn!("Inner"), Ty::U8), (tn!("Outer"), outer.clone())]);
+        let overlay = lib([(tn!("Inner"), Ty::U16)]);
+
+        let mut merged = base.clone();
+        assert_eq!(
+            merged.merge_with(overlay, MergePolicy::PreferOther),
+            Err(MergeError::DanglingRef {
+                within: tn!("Outer"),
+                unknown: inner_id
+            })
+        );
+        assert_eq!(merged, base);
+
+        let mut merged = lib([(tn!("Inner"), Ty::U16)]);
+        assert_eq!(
+            merged.merge_with(base.clone(), MergePolicy::PreferSelf),
+            Err(MergeError::DanglingRef {
+                within: tn!("Outer"),
+                unknown: inner_id
+            })
+        );
+
+        let mut merged = lib([(tn!("Inner"), Ty::U8)]);
+        let report = merged.merge_with(base.clone(), MergePolicy::PreferSelf).unwrap();
+        assert_eq!(report.added, bset! { tn!("Outer") });
+        assert_eq!(merged.types, base.types);
+    }
+
+    #[test]
+    fn merge_dependency_conflict() {
+        let std = std_stl();
+        let mut base = lib([(tn!("Inner"), Ty::U8)]);
+        base.dependencies.push(std.to_dependency()).unwrap();
+
+        let mut other = base.clone();
+        other.dependencies = none!();
+        let renamed = Dependency::with(std.id(), libname!("Another"));
+        other.dependencies.push(renamed.clone()).unwrap();
+        assert_eq!(
+            base.clone().merge_with(other.clone(), MergePolicy::Strict),
+            Err(MergeError::DependencyConflict(renamed))
+        );
+
+        let forged = Dependency::with(strict_types_stl().id(), std.name.clone());
+        other.dependencies = none!();
+        other.dependencies.push(forged.clone()).unwrap();
+        assert_eq!(
+            base.clone().merge_with(other, MergePolicy::Strict),
+            Err(MergeError::DependencyConflict(forged))
+        );
+
+        let mut merged = base.clone();
+        merged.merge_with(base.clone(), MergePolicy::Strict).unwrap();
+        assert_eq!(merged, base);
+    }
+}
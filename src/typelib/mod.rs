@@ -24,22 +24,41 @@ mod id;
 mod type_lib;
 mod compile;
 mod serialize;
+mod parse;
 mod transpile;
 mod symbolic;
 mod translate;
+mod render;
+mod merge;
+mod diff;
+mod export;
+mod compat;
+mod stats;
 
+pub use compat::{CompatResult, Incompatibility};
 pub(crate) use compile::NestedContext;
 #[allow(deprecated)]
 pub use compile::TranslateError;
 pub use compile::{CompileError, TypeIndex};
-pub use id::TypeLibId;
+pub use diff::{DepSet, TypeLibDiff};
+pub use export::ExportError;
+#[cfg(feature = "serde")]
+pub use export::JSON_SCHEMA_DIALECT;
+pub use id::{TypeLibId, LIB_ID_TAG};
+pub use merge::{MergeError, MergePolicy, MergeReport};
+pub use parse::LibParseError;
+pub use serialize::{LibDeserializeError, LoadError, CURRENT_FORMAT_VERSION};
+pub use stats::TypeLibStats;
 pub use symbolic::{ExternTypes, SymbolRef, SymbolicLib, TranspileError, TranspileRef};
 use translate::SymbolContext;
 pub use translate::SymbolError;
-pub use transpile::LibBuilder;
+pub use transpile::{LibBuilder, Registered, StrictTypeRegistry};
+#[cfg(any(test, feature = "test-helpers"))]
+pub use type_lib::assert_stable_transpile;
 pub(crate) use type_lib::TypeMap;
 pub use type_lib::{
-    Dependency, ExternRef, InlineRef, InlineRef1, InlineRef2, LibRef, LibSubref, TypeLib,
+    cmp_by_name_then_id, name_collisions, Dependency, ExternRef, InlineRef, InlineRef1, InlineRef2,
+    Leaf, LibRef, LibSubref, TypeLib, TypeMapError, UnknownTypeName,
 };
 
 #[deprecated(since = "1.3.0", note = "import from the crate root")]
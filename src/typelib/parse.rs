@@ -0,0 +1,935 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of the textual type library representation, as produced by the `Display`
+//! implementation of [`TypeLib`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use amplify::confinement::{self, Confined};
+use encoding::{FieldName, LibName, Primitive, Sizing, TypeName, Variant, VariantName};
+
+use crate::ast::{EnumVariants, Field, NamedFields, UnionVariants, UnnamedFields};
+use crate::typelib::{ExternRef, InlineRef, InlineRef1, InlineRef2, LibRef};
+use crate::{Dependency, PrimitiveExt, SemId, SizingExt, Ty, TypeLib, TypeLibId, TypeRef};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LibParseError {
+    /// unexpected `{found}` at {line}:{col}, while {expected} was expected.
+    Unexpected {
+        line: usize,
+        col: usize,
+        found: String,
+        expected: &'static str,
+    },
+
+    /// invalid {what} at {line}:{col}.
+    Invalid {
+        line: usize,
+        col: usize,
+        what: String,
+    },
+
+    /// types refer to library {0} which is not imported.
+    UnknownLib(TypeLibId),
+
+    /// definition of type `{name}` doesn't match its declared semantic id {declared}.
+    TypeIdMismatch { name: TypeName, declared: SemId },
+
+    /// library id {computed} computed from the source doesn't match the declared id
+    /// {declared}.
+    IdMismatch {
+        declared: TypeLibId,
+        computed: TypeLibId,
+    },
+
+    #[display(inner)]
+    #[from]
+    Confinement(confinement::Error),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Tok {
+    Word(String),
+    Num(u64),
+    SemId(String),
+    LibId(String),
+    Sym(&'static str),
+    Eof,
+}
+
+impl Display for Tok {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Tok::Word(s) | Tok::SemId(s) | Tok::LibId(s) => f.write_str(s),
+            Tok::Num(n) => write!(f, "{n}"),
+            Tok::Sym(s) => f.write_str(s),
+            Tok::Eof => f.write_str("end of input"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    tok: Tok,
+    line: usize,
+    col: usize,
+}
+
+const SYMBOLS: [&str; 16] =
+    ["->", "..", ":", ",", "|", "(", ")", "[", "]", "{", "}", "^", "?", "#", ".", "-"];
+
+/// Comment text together with the line it appears at and the index of the token following it,
+/// which ties the comment to the surrounding constructs independently of the line breaks.
+#[derive(Clone, Debug)]
+struct Comment {
+    text: String,
+    line: usize,
+    token: usize,
+}
+
+/// Splits the source into tokens, collecting `--` comments separately.
+fn lex(s: &str) -> Result<(Vec<Token>, Vec<Comment>), LibParseError> {
+    let mut tokens = vec![];
+    let mut comments = vec![];
+    for (line, text) in s.lines().enumerate() {
+        let line = line + 1;
+        let mut rest = text;
+        loop {
+            let trimmed = rest.trim_start();
+            if trimmed.is_empty() {
+                break;
+            }
+            let col = text.len() - trimmed.len() + 1;
+            if let Some(comment) = trimmed.strip_prefix("--") {
+                comments.push(Comment {
+                    text: comment.trim().to_owned(),
+                    line,
+                    token: tokens.len(),
+                });
+                break;
+            }
+            let word_len = trimmed
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(trimmed.len());
+            let (tok, len) = if word_len > 0 {
+                let word = &trimmed[..word_len];
+                let is_id = matches!(word, "semid" | "stl") && trimmed[word_len..].starts_with(':');
+                if is_id {
+                    let len = trimmed
+                        .find(|c: char| !c.is_ascii_alphanumeric() && !"!$-#:".contains(c))
+                        .unwrap_or(trimmed.len());
+                    let id = trimmed[..len].to_owned();
+                    (if word == "stl" { Tok::LibId(id) } else { Tok::SemId(id) }, len)
+                } else if word.starts_with(|c: char| c.is_ascii_digit()) {
+                    let num = match word.strip_prefix("0x") {
+                        Some(hex) => u64::from_str_radix(hex, 16),
+                        None => u64::from_str(word),
+                    };
+                    let num = num.map_err(|_| LibParseError::Invalid {
+                        line,
+                        col,
+                        what: format!("number `{word}`"),
+                    })?;
+                    (Tok::Num(num), word_len)
+                } else {
+                    (Tok::Word(word.to_owned()), word_len)
+                }
+            } else {
+                let sym =
+                    SYMBOLS.into_iter().find(|sym| trimmed.starts_with(sym)).ok_or_else(|| {
+                        LibParseError::Unexpected {
+                            line,
+                            col,
+                            found: trimmed.chars().next().expect("non-empty").to_string(),
+                            expected: "type library syntax",
+                        }
+                    })?;
+                (Tok::Sym(sym), sym.len())
+            };
+            tokens.push(Token { tok, line, col });
+            rest = &trimmed[len..];
+        }
+    }
+    let line = s.lines().count() + 1;
+    tokens.push(Token {
+        tok: Tok::Eof,
+        line,
+        col: 1,
+    });
+    Ok((tokens, comments))
+}
+
+/// Detects words naming types, as opposed to the field and variant names.
+fn is_type_word(word: &str) -> bool {
+    word == "Unicode" || Primitive::from_display_name(word).is_ok()
+}
+
+/// Library type reference which can be constructed by the parser at a given inlining depth.
+trait ParseRef: TypeRef {
+    type Next: ParseRef;
+    fn inline(ty: Ty<Self::Next>) -> Option<Self>;
+    fn named(sem_id: SemId) -> Self;
+    fn external(ext: ExternRef) -> Self;
+}
+
+impl ParseRef for LibRef {
+    type Next = InlineRef;
+    fn inline(ty: Ty<InlineRef>) -> Option<Self> { Some(LibRef::Inline(ty)) }
+    fn named(sem_id: SemId) -> Self { LibRef::Named(sem_id) }
+    fn external(ext: ExternRef) -> Self { LibRef::Extern(ext) }
+}
+
+impl ParseRef for InlineRef {
+    type Next = InlineRef1;
+    fn inline(ty: Ty<InlineRef1>) -> Option<Self> { Some(InlineRef::Inline(ty)) }
+    fn named(sem_id: SemId) -> Self { InlineRef::Named(sem_id) }
+    fn external(ext: ExternRef) -> Self { InlineRef::Extern(ext) }
+}
+
+impl ParseRef for InlineRef1 {
+    type Next = InlineRef2;
+    fn inline(ty: Ty<InlineRef2>) -> Option<Self> { Some(InlineRef1::Inline(ty)) }
+    fn named(sem_id: SemId) -> Self { InlineRef1::Named(sem_id) }
+    fn external(ext: ExternRef) -> Self { InlineRef1::Extern(ext) }
+}
+
+impl ParseRef for InlineRef2 {
+    type Next = InlineRef2;
+    fn inline(_: Ty<InlineRef2>) -> Option<Self> { None }
+    fn named(sem_id: SemId) -> Self { InlineRef2::Named(sem_id) }
+    fn external(ext: ExternRef) -> Self { InlineRef2::Extern(ext) }
+}
+
+/// Ways of reading the constructs, which are displayed in the same way for different types.
+///
+/// The default reading matches the types produced by the derived strict encoding: top-level
+/// single types are newtypes, and payloads of the union variants and optionals are tuples with
+/// a single field.
+#[derive(Copy, Clone, Default)]
+struct Reading {
+    /// Read a single type at the top level as is instead of a newtype wrapping it, and a tuple
+    /// at the top level as a newtype wrapping an inline tuple.
+    bare_top: bool,
+    /// Read `[T ^ N]` as a list of the fixed size instead of an array.
+    fixed_list: bool,
+    /// Read a single named member at the top level as a union variant instead of a field.
+    union: bool,
+    /// Read a single union variant payload as is instead of a tuple wrapping it.
+    bare_payloads: bool,
+    /// Read an optional payload as is instead of a tuple wrapping it.
+    bare_options: bool,
+}
+
+impl Reading {
+    const ALL: [Reading; 32] = {
+        let mut all = [Reading {
+            bare_top: false,
+            fixed_list: false,
+            union: false,
+            bare_payloads: false,
+            bare_options: false,
+        }; 32];
+        let mut i = 0;
+        while i < 32 {
+            all[i] = Reading {
+                bare_top: i & 1 != 0,
+                fixed_list: i & 2 != 0,
+                union: i & 4 != 0,
+                bare_payloads: i & 8 != 0,
+                bare_options: i & 16 != 0,
+            };
+            i += 1;
+        }
+        all
+    };
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    reading: Reading,
+}
+
+impl Parser {
+    fn peek(&self) -> &Tok { self.peek_at(0) }
+    fn peek_at(&self, n: usize) -> &Tok {
+        let pos = (self.pos + n).min(self.tokens.len() - 1);
+        &self.tokens[pos].tok
+    }
+    fn token(&self) -> &Token { &self.tokens[self.pos.min(self.tokens.len() - 1)] }
+    fn next(&mut self) -> Token {
+        let token = self.token().clone();
+        if token.tok != Tok::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn is_sym(&self, sym: &'static str) -> bool { self.peek() == &Tok::Sym(sym) }
+    fn eat_sym(&mut self, sym: &'static str) -> bool {
+        let found = self.is_sym(sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn unexpected(&self, expected: &'static str) -> LibParseError {
+        let token = self.token();
+        LibParseError::Unexpected {
+            line: token.line,
+            col: token.col,
+            found: token.tok.to_string(),
+            expected,
+        }
+    }
+    fn invalid(token: &Token, what: impl Display) -> LibParseError {
+        LibParseError::Invalid {
+            line: token.line,
+            col: token.col,
+            what: what.to_string(),
+        }
+    }
+
+    fn expect_sym(&mut self, sym: &'static str) -> Result<(), LibParseError> {
+        if !self.eat_sym(sym) {
+            return Err(self.unexpected(sym));
+        }
+        Ok(())
+    }
+    fn expect_keyword(&mut self, keyword: &'static str) -> Result<(), LibParseError> {
+        if self.peek() != &Tok::Word(keyword.to_owned()) {
+            return Err(self.unexpected(keyword));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+    fn num(&mut self) -> Result<(u64, Token), LibParseError> {
+        match self.peek() {
+            Tok::Num(n) => Ok((*n, self.next())),
+            _ => Err(self.unexpected("number")),
+        }
+    }
+    fn name<T: TryFrom<String>>(&mut self, what: &'static str) -> Result<T, LibParseError> {
+        let Tok::Word(word) = self.peek().clone() else {
+            return Err(self.unexpected(what));
+        };
+        let token = self.next();
+        T::try_from(word.clone())
+            .map_err(|_| Self::invalid(&token, format_args!("{what} `{word}`")))
+    }
+    fn sem_id(&mut self) -> Result<SemId, LibParseError> {
+        let Tok::SemId(s) = self.peek().clone() else {
+            return Err(self.unexpected("semantic type id"));
+        };
+        let token = self.next();
+        SemId::from_str(&s)
+            .map_err(|err| Self::invalid(&token, format_args!("type id `{s}`: {err}")))
+    }
+    fn lib_id(&mut self) -> Result<TypeLibId, LibParseError> {
+        let Tok::LibId(s) = self.peek().clone() else {
+            return Err(self.unexpected("library id"));
+        };
+        let token = self.next();
+        TypeLibId::from_str(&s)
+            .map_err(|err| Self::invalid(&token, format_args!("library id `{s}`: {err}")))
+    }
+
+    /// Detects the end of the type definition: end of the input, closing parenthesis or the
+    /// start of the next type definition.
+    fn is_terminator(&self) -> bool {
+        match self.peek() {
+            Tok::Eof | Tok::Sym(")") => true,
+            Tok::Word(word) => {
+                word == "data"
+                    && matches!(self.peek_at(1), Tok::Word(_))
+                    && self.peek_at(2) == &Tok::Sym(":")
+            }
+            _ => false,
+        }
+    }
+
+    /// Detects a named or extern reference which is not wrapped into an optional.
+    fn is_plain_ref(&self) -> bool {
+        match self.peek() {
+            Tok::SemId(_) => self.peek_at(1) != &Tok::Sym("?"),
+            Tok::LibId(_) => self.peek_at(3) != &Tok::Sym("?"),
+            _ => false,
+        }
+    }
+
+    fn ref_atom<R: ParseRef>(&mut self) -> Result<R, LibParseError> {
+        if let Tok::LibId(_) = self.peek() {
+            let lib_id = self.lib_id()?;
+            self.expect_sym(".")?;
+            let sem_id = self.sem_id()?;
+            return Ok(R::external(ExternRef::with(lib_id, sem_id)));
+        }
+        Ok(R::named(self.sem_id()?))
+    }
+
+    fn inline<R: ParseRef>(&self, ty: Ty<R::Next>, at: &Token) -> Result<R, LibParseError> {
+        R::inline(ty).ok_or_else(|| Self::invalid(at, "type nesting exceeding the depth limit"))
+    }
+
+    /// Parses the type at the position of a field, variant payload, tuple or collection item.
+    fn reference<R: ParseRef>(&mut self) -> Result<R, LibParseError> {
+        if self.is_plain_ref() {
+            return self.ref_atom();
+        }
+        let at = self.token().clone();
+        let ty = if self.is_sym("(") && self.peek_at(1) != &Tok::Sym(")") {
+            self.pos += 1;
+            let ty = self.compound::<R::Next>(false)?;
+            self.expect_sym(")")?;
+            ty
+        } else {
+            self.single::<R::Next>()?
+        };
+        self.inline(ty, &at)
+    }
+
+    /// Wraps a type into a tuple with a single field.
+    fn newtype<R: ParseRef>(&self, inner: R::Next, at: &Token) -> Result<R, LibParseError> {
+        let fields = UnnamedFields::try_from(vec![inner])?;
+        self.inline(Ty::Tuple(fields), at)
+    }
+
+    fn option<R: ParseRef>(&self, some: R, at: &Token) -> Result<Ty<R>, LibParseError> {
+        let unit = self.inline(Ty::UNIT, at)?;
+        let variants = UnionVariants::try_from(bmap! {
+            Variant::none() => unit,
+            Variant::some() => some,
+        })?;
+        Ok(Ty::Union(variants))
+    }
+
+    /// Parses a type which is displayed without separators: a primitive, a collection or an
+    /// optional.
+    fn single<R: ParseRef>(&mut self) -> Result<Ty<R>, LibParseError> {
+        let start = self.pos;
+        let at = self.token().clone();
+        if matches!(self.peek(), Tok::SemId(_) | Tok::LibId(_)) {
+            let some = if self.reading.bare_options {
+                self.ref_atom()?
+            } else {
+                let inner = self.ref_atom::<R::Next>()?;
+                self.newtype(inner, &at)?
+            };
+            self.expect_sym("?")?;
+            return self.option(some, &at);
+        }
+        let ty = self.atom::<R>()?;
+        if !self.is_sym("?") {
+            return Ok(ty);
+        }
+        self.pos = start;
+        let some = if self.reading.bare_options {
+            let inner = self.atom::<R::Next>()?;
+            self.inline(inner, &at)?
+        } else {
+            let inner = self.atom::<<R::Next as ParseRef>::Next>()?;
+            let inner = self.inline::<R::Next>(inner, &at)?;
+            self.newtype(inner, &at)?
+        };
+        self.expect_sym("?")?;
+        self.option(some, &at)
+    }
+
+    fn sizing(&mut self) -> Result<Sizing, LibParseError> {
+        let at = self.token().clone();
+        let (min, max) = if self.eat_sym("..") {
+            (0, self.num()?.0)
+        } else {
+            let (min, _) = self.num()?;
+            if !self.eat_sym("..") {
+                (min, min)
+            } else if let Tok::Num(_) = self.peek() {
+                (min, self.num()?.0)
+            } else {
+                (min, u16::MAX as u64)
+            }
+        };
+        Sizing::try_new(min, max).map_err(|err| Self::invalid(&at, err))
+    }
+
+    fn atom<R: ParseRef>(&mut self) -> Result<Ty<R>, LibParseError> {
+        let start = self.pos;
+        let token = self.next();
+        Ok(match &token.tok {
+            Tok::Word(word) if word == "Unicode" => Ty::UnicodeChar,
+            Tok::Word(word) => Ty::Primitive(
+                Primitive::from_display_name(word)
+                    .map_err(|_| Self::invalid(&token, format_args!("primitive type `{word}`")))?,
+            ),
+            Tok::Sym("(") => {
+                self.expect_sym(")")?;
+                Ty::UNIT
+            }
+            Tok::Sym("[") => {
+                let item = self.reference::<R>()?;
+                let ty = if !self.eat_sym("^") {
+                    Ty::List(item, Sizing::new(0, u16::MAX as u64))
+                } else if matches!(self.peek(), Tok::Num(_))
+                    && !self.reading.fixed_list
+                    && self.peek_at(1) == &Tok::Sym("]")
+                {
+                    let (len, at) = self.num()?;
+                    let len = u16::try_from(len)
+                        .map_err(|_| Self::invalid(&at, format_args!("array length {len}")))?;
+                    Ty::Array(item, len)
+                } else {
+                    Ty::List(item, self.sizing()?)
+                };
+                self.expect_sym("]")?;
+                ty
+            }
+            Tok::Sym("{") => {
+                let key = self.reference::<R>()?;
+                let map = self.eat_sym("->");
+                let sizing = if self.eat_sym("^") {
+                    self.sizing()?
+                } else {
+                    Sizing::new(0, u16::MAX as u64)
+                };
+                let ty = if map {
+                    Ty::Map(key, self.reference::<R>()?, sizing)
+                } else {
+                    Ty::Set(key, sizing)
+                };
+                self.expect_sym("}")?;
+                ty
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.unexpected("type"));
+            }
+        })
+    }
+
+    /// Parses a type which may consist of several members: a structure, a tuple, a union or an
+    /// enum.
+    fn compound<R: ParseRef>(&mut self, top: bool) -> Result<Ty<R>, LibParseError> {
+        if !matches!(self.peek(), Tok::Word(word) if !is_type_word(word)) {
+            return self.unnamed(top);
+        }
+
+        let mut members = vec![];
+        let mut sep = None;
+        loop {
+            let at = self.token().clone();
+            let Tok::Word(name) = at.tok.clone() else {
+                return Err(self.unexpected("field or variant name"));
+            };
+            self.pos += 1;
+            let tag = if self.eat_sym("#") {
+                let (tag, at) = self.num()?;
+                Some(u8::try_from(tag).map_err(|_| Self::invalid(&at, format_args!("tag {tag}")))?)
+            } else {
+                None
+            };
+            let payload = if self.is_sym("|") || self.is_sym(",") || self.is_terminator() {
+                None
+            } else {
+                Some((self.pos, self.reference::<R>()?))
+            };
+            members.push((name, at, tag, payload));
+            match self.peek() {
+                Tok::Sym(s @ ("," | "|")) if sep.is_none() || sep == Some(*s) => {
+                    sep = Some(*s);
+                    self.pos += 1;
+                }
+                Tok::Sym("," | "|") => return Err(self.unexpected("the same member separator")),
+                _ => break,
+            }
+        }
+
+        let is_struct = match sep {
+            Some(",") => true,
+            None => !(top && self.reading.union) && members[0].2.is_none(),
+            _ => false,
+        };
+        if is_struct
+            && members.iter().all(|(_, _, tag, payload)| tag.is_none() && payload.is_some())
+        {
+            let mut fields = Vec::with_capacity(members.len());
+            for (name, at, _, ty) in members {
+                let name = FieldName::try_from(name.clone())
+                    .map_err(|_| Self::invalid(&at, format_args!("field name `{name}`")))?;
+                fields.push(Field {
+                    name,
+                    ty: ty.expect("checked above").1,
+                });
+            }
+            return Ok(Ty::Struct(NamedFields::try_from(fields)?));
+        }
+
+        let mut next_tag = 0u8;
+        let mut variants = Vec::with_capacity(members.len());
+        for (name, at, tag, payload) in members {
+            let name = VariantName::try_from(name.clone())
+                .map_err(|_| Self::invalid(&at, format_args!("variant name `{name}`")))?;
+            let tag = tag.unwrap_or(next_tag);
+            next_tag = tag.saturating_add(1);
+            variants.push((Variant::named(tag, name), payload, at));
+        }
+        if variants.iter().all(|(_, payload, _)| payload.is_none()) {
+            let variants =
+                variants.into_iter().map(|(variant, ..)| variant).collect::<BTreeSet<_>>();
+            return Ok(Ty::Enum(EnumVariants::try_from(variants)?));
+        }
+        let end = self.pos;
+        let mut union = BTreeMap::new();
+        for (variant, payload, at) in variants {
+            let Some((pos, mut ty)) = payload else {
+                return Err(Self::invalid(
+                    &at,
+                    format_args!("union variant `{}` without type", variant.name),
+                ));
+            };
+            // Unit and parenthesized payloads are displayed with parentheses; the rest are
+            // single-field tuples unless read as is.
+            if !self.reading.bare_payloads && self.tokens[pos].tok != Tok::Sym("(") {
+                self.pos = pos;
+                let inner = self.reference::<R::Next>()?;
+                ty = self.newtype(inner, &at)?;
+            }
+            union.insert(variant, ty);
+        }
+        self.pos = end;
+        Ok(Ty::Union(UnionVariants::try_from(union)?))
+    }
+
+    fn unnamed<R: ParseRef>(&mut self, top: bool) -> Result<Ty<R>, LibParseError> {
+        let start = self.pos;
+        let at = self.token().clone();
+        if !(self.is_plain_ref() || top && !self.reading.bare_top) {
+            let ty = self.single::<R>()?;
+            if !self.is_sym(",") {
+                return Ok(ty);
+            }
+            self.pos = start;
+        }
+        let mut fields = vec![self.reference::<R>()?];
+        while self.eat_sym(",") {
+            fields.push(self.reference::<R>()?);
+        }
+        if fields.len() > 1 && top && self.reading.bare_top {
+            self.pos = start;
+            let mut fields = vec![self.reference::<R::Next>()?];
+            while self.eat_sym(",") {
+                fields.push(self.reference::<R::Next>()?);
+            }
+            let inner = self.inline(Ty::Tuple(UnnamedFields::try_from(fields)?), &at)?;
+            return Ok(Ty::Tuple(UnnamedFields::try_from(vec![inner])?));
+        }
+        Ok(Ty::Tuple(UnnamedFields::try_from(fields)?))
+    }
+
+    /// Parses type definition body, returning all readings which are consistent with the
+    /// source. The first reading is the default one.
+    fn definition(&mut self) -> Result<Vec<Ty<LibRef>>, LibParseError> {
+        let start = self.pos;
+        let mut end = start;
+        let mut readings = Vec::<Ty<LibRef>>::with_capacity(1);
+        for reading in Reading::ALL {
+            self.pos = start;
+            self.reading = reading;
+            let res = self.compound::<LibRef>(true).and_then(|ty| {
+                if !self.is_terminator() || self.is_sym(")") {
+                    return Err(self.unexpected("end of type definition"));
+                }
+                Ok(ty)
+            });
+            match res {
+                Ok(ty) if readings.is_empty() => {
+                    end = self.pos;
+                    readings.push(ty);
+                }
+                Ok(ty) if self.pos == end && !readings.contains(&ty) => readings.push(ty),
+                Ok(_) => {}
+                Err(err) if readings.is_empty() => return Err(err),
+                Err(_) => {}
+            }
+        }
+        self.pos = end;
+        self.reading = Reading::default();
+        Ok(readings)
+    }
+}
+
+/// Parses the id given in a `-- <id>` comment.
+fn comment_id<Id: FromStr>(comment: &Comment, what: &str) -> Result<Id, LibParseError>
+where Id::Err: Display {
+    Id::from_str(&comment.text).map_err(|err| LibParseError::Invalid {
+        line: comment.line,
+        col: 1,
+        what: format!("{what} id `{}`: {err}", comment.text),
+    })
+}
+
+impl TypeLib {
+    /// Parses type library from the textual representation produced by its `Display`
+    /// implementation. Whitespace between the tokens is insignificant; text after `--` is a
+    /// comment running till the end of the line.
+    ///
+    /// Some constructions are displayed in the same way for different types: a newtype and the
+    /// type it wraps, an array and a list of a fixed size, or a structure with a single field and
+    /// a union with a single variant. The ambiguities are resolved by taking the reading matching
+    /// the semantic id referenced from the other types. Alternatively, the semantic id may be
+    /// given in a `-- semid:...` comment anywhere between the type name and the next definition,
+    /// failing with [`LibParseError::TypeIdMismatch`] if none of the readings matches it.
+    ///
+    /// If the comment following the `typelib` header provides the library id, the readings of the
+    /// remaining ambiguous types, which are not referenced by any other type, are chosen to match
+    /// it, failing with [`LibParseError::IdMismatch`] if there is no such combination. Without the
+    /// library id these types follow the layout of the types with derived strict encoding.
+    ///
+    /// Names of the types imported from other libraries are not a part of the textual
+    /// representation, thus the returned library has no [`TypeLib::extern_types`] index.
+    pub fn from_source(s: &str) -> Result<TypeLib, LibParseError> {
+        let (tokens, comments) = lex(s)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            reading: Reading::default(),
+        };
+
+        parser.expect_keyword("typelib")?;
+        let name = parser.name::<LibName>("library name")?;
+        let declared = comments
+            .iter()
+            .filter(|comment| comment.token == parser.pos)
+            .find(|comment| comment.text.starts_with("stl:"))
+            .map(|comment| comment_id::<TypeLibId>(comment, "library"))
+            .transpose()?;
+
+        let mut dependencies = BTreeSet::new();
+        while parser.peek() == &Tok::Word(s!("import")) {
+            parser.pos += 1;
+            let lib = parser.name::<LibName>("library name")?;
+            let id = parser.lib_id()?;
+            dependencies.insert(Dependency::with(id, lib));
+        }
+        let body = parser.pos;
+
+        let mut types = BTreeMap::new();
+        while parser.peek() != &Tok::Eof {
+            parser.expect_keyword("data")?;
+            let name = parser.name::<TypeName>("type name")?;
+            parser.expect_sym(":")?;
+            let start = parser.pos;
+            let readings = parser.definition()?;
+            let declared = comments
+                .iter()
+                .filter(|comment| (start..=parser.pos).contains(&comment.token))
+                .find(|comment| comment.text.starts_with("semid:"))
+                .map(|comment| comment_id::<SemId>(comment, "type"))
+                .transpose()?;
+            if types.insert(name.clone(), (readings, declared)).is_some() {
+                return Err(Parser::invalid(
+                    parser.token(),
+                    format_args!("repeated type `{name}`"),
+                ));
+            }
+        }
+
+        let mut referenced = BTreeSet::new();
+        parser.pos = body;
+        while parser.peek() != &Tok::Eof {
+            match parser.peek() {
+                Tok::LibId(_) => {
+                    let id = parser.lib_id()?;
+                    if !dependencies.iter().any(|dep| dep.id == id) {
+                        return Err(LibParseError::UnknownLib(id));
+                    }
+                }
+                Tok::SemId(_) => {
+                    referenced.insert(parser.sem_id()?);
+                }
+                _ => parser.pos += 1,
+            }
+        }
+
+        let mut resolved = BTreeMap::new();
+        let mut roots = vec![];
+        for (name, (mut readings, declared)) in types {
+            let pos = match declared {
+                Some(declared) => readings
+                    .iter()
+                    .position(|ty| ty.sem_id_named(&name) == declared)
+                    .ok_or_else(|| LibParseError::TypeIdMismatch {
+                        name: name.clone(),
+                        declared,
+                    })?,
+                None => match readings
+                    .iter()
+                    .position(|ty| referenced.contains(&ty.sem_id_named(&name)))
+                {
+                    Some(pos) => pos,
+                    None if readings.len() > 1 => {
+                        resolved.insert(name.clone(), readings[0].clone());
+                        roots.push((name, readings));
+                        continue;
+                    }
+                    None => 0,
+                },
+            };
+            resolved.insert(name, readings.swap_remove(pos));
+        }
+
+        let mut lib = TypeLib {
+            name,
+            dependencies: Confined::try_from(dependencies)?,
+            extern_types: none!(),
+            types: Confined::try_from(resolved)?,
+        };
+        let Some(declared) = declared else {
+            return Ok(lib);
+        };
+        // Ambiguous types which are neither referenced nor annotated with their ids are
+        // constrained only by the library id, so their readings are chosen to match it.
+        let computed = lib.id();
+        let mut choice = vec![0usize; roots.len()];
+        while lib.id() != declared {
+            let Some(next) =
+                choice.iter().zip(&roots).position(|(pos, (_, readings))| pos + 1 < readings.len())
+            else {
+                return Err(LibParseError::IdMismatch { declared, computed });
+            };
+            for (pos, (name, readings)) in choice.iter_mut().zip(&roots).take(next + 1) {
+                *pos = if *pos + 1 < readings.len() { *pos + 1 } else { 0 };
+                lib.types.insert(name.clone(), readings[*pos].clone())?;
+            }
+        }
+        Ok(lib)
+    }
+}
+
+impl FromStr for TypeLib {
+    type Err = LibParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> { TypeLib::from_source(s) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+
+    #[test]
+    fn roundtrip() {
+        for lib in [std_stl(), strict_types_stl()] {
+            let expected = TypeLib {
+                extern_types: none!(),
+                ..lib.clone()
+            };
+            for source in
+                [lib.to_string(), format!("{lib:40}"), lib.to_string().replace(", ", " ,\n\n  ")]
+            {
+                let parsed = TypeLib::from_source(&source).unwrap();
+                assert_eq!(parsed, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn unused_import() {
+        let mut lib = TypeLib::single(libname!("Test"), tn!("Byte"), Ty::U8).unwrap();
+        lib.import(std_stl().to_dependency()).unwrap();
+        assert_eq!(TypeLib::from_source(&lib.to_string()).unwrap(), lib);
+    }
+
+    #[test]
+    fn type_id_comments() {
+        // A bare primitive reads as a newtype wrapping it unless its id is known
+        let lib = TypeLib::single(libname!("Test"), tn!("Byte"), Ty::U8).unwrap();
+        let sem_id = Ty::<LibRef>::U8.sem_id_named(&tn!("Byte"));
+        let source = lib.to_string();
+        assert_eq!(TypeLib::from_source(&source).unwrap(), lib);
+        let source = source.replace(&format!(" -- {}", lib.id()), "");
+        assert_ne!(TypeLib::from_source(&source).unwrap(), lib);
+
+        let same_line = source.replace(" : U8", &format!(" : U8 -- {sem_id}"));
+        assert_eq!(TypeLib::from_source(&same_line).unwrap(), lib);
+        let next_line = format!("{source}\n  -- {sem_id}\n");
+        assert_eq!(TypeLib::from_source(&next_line).unwrap(), lib);
+
+        let other = Ty::<LibRef>::U16.sem_id_named(&tn!("Byte"));
+        let source = format!("{source}-- {other}\n");
+        assert_eq!(TypeLib::from_source(&source).unwrap_err(), LibParseError::TypeIdMismatch {
+            name: tn!("Byte"),
+            declared: other
+        });
+    }
+
+    #[test]
+    fn errors() {
+        let err = TypeLib::from_source("typelib Test\n\ndata Foo : [U7]").unwrap_err();
+        assert_eq!(err, LibParseError::Invalid {
+            line: 3,
+            col: 13,
+            what: s!("primitive type `U7`")
+        });
+
+        let err = TypeLib::from_source("typelib Test\ndata Foo : [U8 ^ ..0xff\n").unwrap_err();
+        assert_eq!(err, LibParseError::Unexpected {
+            line: 3,
+            col: 1,
+            found: s!("end of input"),
+            expected: "]"
+        });
+
+        let err = TypeLib::from_source("typelib Test\n\ndata Foo : ").unwrap_err();
+        assert_eq!(err, LibParseError::Unexpected {
+            line: 4,
+            col: 1,
+            found: s!("end of input"),
+            expected: "type"
+        });
+
+        let lib = strict_types_stl();
+        let sizing = lib.types.get(&tn!("Sizing")).unwrap().sem_id_named(&tn!("Sizing"));
+        let source = format!("{lib:17}")
+            .replace("data Sizing            : min U64", "data Sizing : min U32");
+        assert!(matches!(
+            TypeLib::from_source(&source).unwrap_err(),
+            LibParseError::IdMismatch { declared, .. } if declared == lib.id()
+        ));
+        let source = source.replace("data Sizing : ", &format!("data Sizing : -- {sizing}\n"));
+        assert_eq!(TypeLib::from_source(&source).unwrap_err(), LibParseError::TypeIdMismatch {
+            name: tn!("Sizing"),
+            declared: sizing
+        });
+
+        let source = lib
+            .to_string()
+            .lines()
+            .filter(|line| !line.starts_with("import "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(matches!(TypeLib::from_source(&source).unwrap_err(), LibParseError::UnknownLib(_)));
+    }
+}
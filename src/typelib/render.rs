@@ -0,0 +1,355 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Human-readable rendering of library types and schema-aware rendering of strict values.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+use amplify::hex::ToHex;
+use strict_encoding::TypeName;
+
+use crate::ast::Path;
+use crate::typelib::LibSubref;
+use crate::value::EnumTag;
+use crate::{LibRef, SemId, StrictVal, Ty, TypeLib};
+
+impl TypeLib {
+    /// Explains which type of the library has the provided semantic id, returning its name (or
+    /// the path to the first place where the type is defined, for inline types), the list of
+    /// paths where the type is used and its definition.
+    ///
+    /// Returns `None` if the library doesn't contain type with the provided id.
+    pub fn explain(&self, id: SemId) -> Option<String> {
+        let mut found = self
+            .types
+            .iter()
+            .find(|(name, ty)| ty.sem_id_named(name) == id)
+            .map(|(name, ty)| (name.to_string(), ty.to_string()));
+        let mut usages = vec![];
+        for (name, ty) in &self.types {
+            let mut path = vec![name.to_string()];
+            explain_walk(ty, id, &mut path, &mut found, &mut usages);
+        }
+        let (name, def) = found?;
+
+        let mut s = format!("type {name} -- {id}\n");
+        s.push_str(&format!("  defined in library {}\n", self.name));
+        if usages.is_empty() {
+            s.push_str("  not used by other types of the library\n");
+        } else {
+            s.push_str(&format!("  used in {}\n", usages.join(", ")));
+        }
+        s.push_str(&format!("  {def}\n"));
+        Some(s)
+    }
+}
+
+impl TypeLib {
+    /// Renders the library as a GraphViz DOT digraph.
+    ///
+    /// Each library type is a node, and each reference to a named type - an edge labeled with
+    /// the path to the field or variant introducing the reference. Types from the dependencies
+    /// are shown as dashed nodes labeled with the dependency name; primitive types have their
+    /// definition rendered in the node label.
+    pub fn to_dot(&self) -> String {
+        let names = self
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), name.to_string()))
+            .chain(self.extern_types.iter().flat_map(|(lib, types)| {
+                types.iter().map(move |(sem_id, name)| (*sem_id, format!("{lib}.{name}")))
+            }))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut s = format!("digraph \"{}\" {{\n", self.name);
+        s.push_str("  node [shape=box];\n");
+        for (name, ty) in &self.types {
+            match ty {
+                Ty::Primitive(_) | Ty::UnicodeChar => {
+                    s.push_str(&format!("  \"{name}\" [label=\"{name}\\n{ty}\"];\n"))
+                }
+                _ => s.push_str(&format!("  \"{name}\";\n")),
+            }
+        }
+        for (lib, types) in &self.extern_types {
+            for name in types.values() {
+                s.push_str(&format!("  \"{lib}.{name}\" [style=dashed];\n"));
+            }
+        }
+        for (name, ty) in &self.types {
+            let mut edges = vec![];
+            dot_edges(ty, &mut Path::new(), &mut edges);
+            for (path, sem_id) in edges {
+                let Some(target) = names.get(&sem_id) else {
+                    continue;
+                };
+                let label = path.to_string();
+                let label = label.trim_start_matches('.');
+                s.push_str(&format!("  \"{name}\" -> \"{target}\" [label=\"{label}\"];\n"));
+            }
+        }
+        s.push_str("}\n");
+        s
+    }
+}
+
+fn dot_edges<Ref: LibSubref>(ty: &Ty<Ref>, path: &mut Path, edges: &mut Vec<(Path, SemId)>) {
+    for (pos, (r, _)) in ty.type_refs().enumerate() {
+        let step = ty.step_at(pos as u8).expect("type reference without path step");
+        path.push(step).expect("type nesting exceeds path length limit");
+        match (r.as_inline(), r.referenced_id()) {
+            (Some(inline), _) => dot_edges(inline, path, edges),
+            (None, Some(sem_id)) => edges.push((path.clone(), sem_id)),
+            (None, None) => unreachable!("library type reference is neither inline nor named"),
+        }
+        path.pop();
+    }
+}
+
+fn explain_walk<Ref: LibSubref>(
+    ty: &Ty<Ref>,
+    id: SemId,
+    path: &mut Vec<String>,
+    found: &mut Option<(String, String)>,
+    usages: &mut Vec<String>,
+) {
+    for (pos, (r, case)) in ty.type_refs().enumerate() {
+        path.push(case.map(|case| case.to_string()).unwrap_or_else(|| pos.to_string()));
+        match r.as_inline() {
+            Some(inline) => {
+                if inline.sem_id_unnamed() == id {
+                    found.get_or_insert_with(|| (path.join("."), inline.to_string()));
+                    usages.push(path.join("."));
+                }
+                explain_walk(inline, id, path, found, usages);
+            }
+            None if r.referenced_id() == Some(id) => usages.push(path.join(".")),
+            None => {}
+        }
+        path.pop();
+    }
+}
+
+type ValResolver<'a, 'lib> = dyn Fn(SemId) -> Option<(&'lib TypeName, &'lib Ty<LibRef>)> + 'a;
+
+impl<Ref: LibSubref> Ty<Ref> {
+    /// Renders strict value using the type information, such that structure fields and enum
+    /// variants are represented with their names, and byte strings - as hex.
+    ///
+    /// Named and external type references are resolved with the provided `resolver`. If a type
+    /// can't be resolved, or the value doesn't match the type, the value is rendered in STON.
+    pub fn render_value<'lib>(
+        &self,
+        val: &StrictVal,
+        resolver: impl Fn(SemId) -> Option<(&'lib TypeName, &'lib Ty<LibRef>)>,
+    ) -> String {
+        let mut s = String::new();
+        self.render_inner(None, val, &resolver, &mut s).expect("writing to string");
+        s
+    }
+
+    fn render_ref<'lib>(
+        r: &Ref,
+        val: &StrictVal,
+        resolver: &ValResolver<'_, 'lib>,
+        f: &mut String,
+    ) -> fmt::Result {
+        if let Some(ty) = r.as_inline() {
+            return ty.render_inner(None, val, resolver, f);
+        }
+        match r.referenced_id().and_then(resolver) {
+            Some((name, ty)) => ty.render_inner(Some(name), val, resolver, f),
+            None => write!(f, "{val}"),
+        }
+    }
+
+    fn render_inner<'lib>(
+        &self,
+        name: Option<&TypeName>,
+        val: &StrictVal,
+        resolver: &ValResolver<'_, 'lib>,
+        f: &mut String,
+    ) -> fmt::Result {
+        match (self, val) {
+            (_, StrictVal::Unit) => f.write_str("()"),
+            (_, StrictVal::Number(num)) => write!(f, "{num}"),
+            (_, StrictVal::String(s)) => write!(f, "{s:?}"),
+            (_, StrictVal::Bytes(bytes)) => write!(f, "0x{}", bytes.to_hex()),
+            (Ty::Enum(variants), StrictVal::Enum(EnumTag::Ord(tag))) => {
+                match variants.name_by_tag(*tag) {
+                    Some(name) => write!(f, "{name}"),
+                    None => write!(f, "{tag}"),
+                }
+            }
+            (Ty::Union(variants), StrictVal::Union(tag, inner)) => {
+                let (variant, ty) = match tag {
+                    EnumTag::Name(name) => (Some(name), variants.ty_by_name(name)),
+                    EnumTag::Ord(tag) => (variants.name_by_tag(*tag), variants.ty_by_tag(*tag)),
+                };
+                match variant {
+                    Some(name) => write!(f, "{name}")?,
+                    None => write!(f, "{tag}")?,
+                }
+                match (ty, inner.as_ref()) {
+                    (_, StrictVal::Unit) => Ok(()),
+                    (Some(ty), inner) => {
+                        f.write_char('(')?;
+                        Self::render_ref(ty, inner, resolver, f)?;
+                        f.write_char(')')
+                    }
+                    (None, inner) => write!(f, "({inner})"),
+                }
+            }
+            (Ty::Struct(fields), StrictVal::Struct(vals)) => {
+                if let Some(name) = name {
+                    write!(f, "{name} ")?;
+                }
+                f.write_str("{ ")?;
+                let mut first = true;
+                for field in fields {
+                    let Some(val) = vals.get(&field.name) else {
+                        continue;
+                    };
+                    if !first {
+                        f.write_str(", ")?;
+                    }
+                    first = false;
+                    write!(f, "{}: ", field.name)?;
+                    Self::render_ref(&field.ty, val, resolver, f)?;
+                }
+                f.write_str(" }")
+            }
+            (Ty::Tuple(fields), StrictVal::Tuple(vals)) => {
+                if let Some(name) = name {
+                    write!(f, "{name}")?;
+                }
+                f.write_char('(')?;
+                for (no, (ty, val)) in fields.iter().zip(vals).enumerate() {
+                    if no > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Self::render_ref(ty, val, resolver, f)?;
+                }
+                f.write_char(')')
+            }
+            (
+                Ty::Array(ty, _) | Ty::List(ty, _) | Ty::Set(ty, _),
+                StrictVal::List(items) | StrictVal::Set(items),
+            ) => {
+                f.write_char('[')?;
+                for (no, item) in items.iter().enumerate() {
+                    if no > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Self::render_ref(ty, item, resolver, f)?;
+                }
+                f.write_char(']')
+            }
+            (Ty::Map(key_ty, ty, _), StrictVal::Map(items)) => {
+                f.write_char('{')?;
+                for (no, (key, item)) in items.iter().enumerate() {
+                    if no > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Self::render_ref(key_ty, key, resolver, f)?;
+                    f.write_str(": ")?;
+                    Self::render_ref(ty, item, resolver, f)?;
+                }
+                f.write_char('}')
+            }
+            (_, val) => write!(f, "{val}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::LibBuilder;
+
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Account {
+        name: TypeName,
+        id: [u8; 4],
+        active: bool,
+    }
+
+    #[test]
+    fn explain() {
+        let lib = strict_types_stl();
+        let ty = lib.types.get(&tn!("TypeName")).unwrap();
+        let explanation = lib.explain(ty.sem_id_named(&tn!("TypeName"))).unwrap();
+        assert!(explanation.starts_with("type TypeName -- "));
+        assert!(explanation.contains("SymbolRef.tyName"));
+        assert_eq!(lib.explain(SemId::from([0xFFu8; 32])), None);
+    }
+
+    #[test]
+    fn render_struct() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Account>()
+            .compile()
+            .unwrap();
+        let index = [&lib, &std, &st]
+            .into_iter()
+            .flat_map(|lib| lib.types.iter())
+            .map(|(name, ty)| (ty.sem_id_named(name), (name, ty)))
+            .collect::<BTreeMap<_, _>>();
+
+        let val = crate::svstruct!(name => "Some", id => crate::svbytes!([0xde, 0xad, 0xbe, 0xef]), active => crate::svenum!(1));
+        let ty = lib.types.get(&tn!("Account")).unwrap();
+        assert_eq!(
+            ty.render_value(&val, |id| index.get(&id).copied()),
+            r#"{ name: "Some", id: 0xdeadbeef, active: true }"#
+        );
+    }
+
+    #[test]
+    fn to_dot() {
+        let std = std_stl();
+        let st = LibBuilder::new(libname!("StrictTypes"), [std.to_dependency()])
+            .transpile::<TypeName>()
+            .compile()
+            .unwrap();
+        assert_eq!(
+            st.to_dot(),
+            r#"digraph "StrictTypes" {
+  node [shape=box];
+  "TypeName";
+  "Std.AlphaNumLodash" [style=dashed];
+  "Std.AlphaCapsLodash" [style=dashed];
+  "TypeName" -> "Std.AlphaCapsLodash" [label="0.0"];
+  "TypeName" -> "Std.AlphaNumLodash" [label="0.1[]"];
+}
+"#
+        );
+        assert!(strict_types_stl()
+            .to_dot()
+            .contains("  \"SymbolRef\" -> \"TypeName\" [label=\"tyName\"];\n"));
+    }
+}
@@ -24,16 +24,118 @@ use std::fmt::{Display, Formatter};
 use std::path::Path;
 use std::{fmt, io};
 
-use amplify::confinement::U24 as U24MAX;
+use amplify::confinement::{Confined, U24 as U24MAX};
+use amplify::hex::ToHex;
+use amplify::ByteArray;
 use baid64::DisplayBaid64;
-use encoding::{StreamWriter, StrictDeserialize, StrictEncode, StrictSerialize, StrictWriter};
+use encoding::{
+    DeserializeError, SerializeError, StreamWriter, StrictDeserialize, StrictEncode,
+    StrictSerialize, StrictWriter,
+};
 
-use crate::{StlFormat, SymbolicLib, TypeLib};
+use crate::{StlFormat, SymbolicLib, TypeLib, TypeLibId};
+
+/// Version of the binary format used by [`TypeLib::to_versioned_serialized`].
+pub const CURRENT_FORMAT_VERSION: u8 = 1;
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LibDeserializeError {
+    /// type library data are empty.
+    Empty,
+
+    /// type library uses unsupported binary format version {0}.
+    UnsupportedVersion(u8),
+
+    #[display(inner)]
+    #[from]
+    Deserialize(DeserializeError),
+}
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LoadError {
+    /// unable to read type library file: {0}
+    #[from]
+    Io(io::Error),
+
+    #[display(inner)]
+    #[from]
+    Deserialize(DeserializeError),
+
+    /// type library file is named after id {expected}, but the library it contains has id
+    /// {actual}.
+    IdMismatch {
+        expected: TypeLibId,
+        actual: TypeLibId,
+    },
+}
 
 impl StrictSerialize for TypeLib {}
 impl StrictDeserialize for TypeLib {}
 
 impl TypeLib {
+    /// Serializes the library into binary strict encoding prefixed with a byte specifying the
+    /// format version ([`CURRENT_FORMAT_VERSION`]).
+    ///
+    /// Unlike [`StrictSerialize::to_strict_serialized`], the data produced by this method can be
+    /// safely cached, since future changes to the format will be detected on deserialization.
+    pub fn to_versioned_serialized(&self) -> Result<Vec<u8>, SerializeError> {
+        let data = self.to_strict_serialized::<U24MAX>()?;
+        let mut buf = Vec::with_capacity(data.len() + 1);
+        buf.push(CURRENT_FORMAT_VERSION);
+        buf.extend(data);
+        Ok(buf)
+    }
+
+    /// Deserializes the library from the data produced by [`TypeLib::to_versioned_serialized`],
+    /// checking the format version.
+    pub fn from_versioned_serialized(data: &[u8]) -> Result<Self, LibDeserializeError> {
+        let (version, data) = data.split_first().ok_or(LibDeserializeError::Empty)?;
+        if *version != CURRENT_FORMAT_VERSION {
+            return Err(LibDeserializeError::UnsupportedVersion(*version));
+        }
+        let data = Confined::try_from(data.to_vec())
+            .map_err(|err| DeserializeError::Decode(err.into()))?;
+        Self::from_strict_serialized::<U24MAX>(data).map_err(LibDeserializeError::from)
+    }
+}
+
+impl TypeLib {
+    fn store_path(dir: &Path, id: TypeLibId) -> std::path::PathBuf {
+        dir.join(format!("{}.stl", id.to_byte_array().to_hex()))
+    }
+
+    /// Saves the strict-serialized library into a content-addressed store located at `dir`,
+    /// using the library id as the file name. Returns the library id.
+    pub fn store(&self, dir: &Path) -> io::Result<TypeLibId> {
+        let id = self.id();
+        let data = self
+            .to_strict_serialized::<U24MAX>()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        std::fs::write(Self::store_path(dir, id), data.as_slice())?;
+        Ok(id)
+    }
+
+    /// Loads library with the given id from a content-addressed store located at `dir`,
+    /// written by [`TypeLib::store`].
+    ///
+    /// Verifies that the loaded library has the expected id, detecting corrupted or mislabeled
+    /// files.
+    pub fn load(dir: &Path, id: TypeLibId) -> Result<Self, LoadError> {
+        let data = std::fs::read(Self::store_path(dir, id))?;
+        let data = Confined::try_from(data).map_err(|err| DeserializeError::Decode(err.into()))?;
+        let lib = Self::from_strict_serialized::<U24MAX>(data)?;
+        let actual = lib.id();
+        if actual != id {
+            return Err(LoadError::IdMismatch {
+                expected: id,
+                actual,
+            });
+        }
+        Ok(lib)
+    }
+
     pub fn serialize(
         &self,
         format: StlFormat,
@@ -130,41 +232,113 @@ impl Display for SymbolicLib {
             f.write_str("-- no dependencies\n")?;
         }
         writeln!(f)?;
-        let width = f.width().unwrap_or(17);
+        let width = f.width().unwrap_or_else(|| {
+            self.types().keys().map(|name| name.len()).max().unwrap_or_default().max(17)
+        });
         for (name, ty) in self.types() {
             if !f.alternate() {
                 let mnemo = ty.sem_id_named(name).to_baid64_mnemonic();
                 writeln!(f, "@mnemonic({mnemo})")?;
             }
-            write!(f, "data {name:0$} : ", width)?;
-            Display::fmt(ty, f)?;
-            writeln!(f)?;
+            writeln!(f, "data {name:0$} : {1}", width, ty.display_at(width + 8))?;
             writeln!(f)?;
         }
         Ok(())
     }
 }
 
-impl Display for TypeLib {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        writeln!(f, "typelib {} -- {}", self.name, self.id())?;
-        writeln!(f)?;
+impl TypeLib {
+    /// Writes textual representation of the library (the same as produced by its [`Display`]
+    /// implementation) to `w` type by type, without materializing it as a single string.
+    ///
+    /// To write into [`io::Write`] one may use `write!(writer, "{lib}")`, which streams the
+    /// data in the same way.
+    pub fn write_to(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_aligned(w, self.name_width())
+    }
+
+    /// Width of the type name column: 17 characters, or the length of the longest type name,
+    /// if it doesn't fit.
+    fn name_width(&self) -> usize {
+        self.types.keys().map(|name| name.len()).max().unwrap_or_default().max(17)
+    }
+
+    fn write_aligned(&self, w: &mut impl fmt::Write, width: usize) -> fmt::Result {
+        writeln!(w, "typelib {} -- {}", self.name, self.id())?;
+        writeln!(w)?;
         for dep in &self.dependencies {
-            writeln!(f, "import {dep}")?;
+            writeln!(w, "import {} {}", dep.name, dep.id)?;
         }
         if self.dependencies.is_empty() {
-            f.write_str("-- no dependencies")?;
+            w.write_str("-- no dependencies")?;
         }
-        writeln!(f)?;
-        writeln!(f)?;
-        let width = f.width().unwrap_or(17);
+        writeln!(w)?;
+        writeln!(w)?;
         for (name, ty) in &self.types {
-            writeln!(f, "data {name:0$} : {ty}\n", width)?;
+            writeln!(w, "data {name:0$} : {1}\n", width, ty.display_at(width + 8))?;
         }
         Ok(())
     }
 }
 
+impl Display for TypeLib {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_else(|| self.name_width());
+        self.write_aligned(f, width)
+    }
+}
+
+impl TypeLib {
+    /// Produces deterministic textual snapshot of the library, suitable for
+    /// regression testing with a VCS diff.
+    ///
+    /// Unlike [`Display`], the snapshot lists full library and dependency ids
+    /// and the semantic id of each of the types, such that any change to the
+    /// library semantics results in a change to the snapshot text.
+    pub fn to_snapshot_string(&self) -> String {
+        let mut s = format!("typelib {}\nid {}\n\n", self.name, self.id());
+        for dep in &self.dependencies {
+            s.push_str(&format!("import {} {}\n", dep.name, dep.id));
+        }
+        if !self.dependencies.is_empty() {
+            s.push('\n');
+        }
+        for (name, ty) in &self.types {
+            let sem_id = ty.sem_id_named(name);
+            s.push_str(&format!("data {name} {sem_id}\n    {}\n", ty.display_at(4)));
+        }
+        s
+    }
+
+    /// Compares the library [snapshot](TypeLib::to_snapshot_string) with the golden file at
+    /// `path`, panicking if they differ.
+    ///
+    /// If `STRICT_TYPES_UPDATE_SNAPSHOTS` environment variable is set, the golden file is
+    /// (re)written with the current snapshot instead.
+    #[cfg(any(test, feature = "test-helpers"))]
+    #[track_caller]
+    pub fn assert_snapshot(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let snapshot = self.to_snapshot_string();
+        if std::env::var_os("STRICT_TYPES_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(path, snapshot).expect("unable to write library snapshot");
+            return;
+        }
+        let golden = std::fs::read_to_string(path).unwrap_or_else(|err| {
+            panic!("unable to read library snapshot from '{}': {err}", path.display())
+        });
+        if golden != snapshot {
+            panic!(
+                "the library {} doesn't match the snapshot in \
+                 '{}'\n\nexpected:\n{golden}\nfound:\n{snapshot}\nif the change in the library \
+                 types is intended, re-run the test with `STRICT_TYPES_UPDATE_SNAPSHOTS=1`",
+                self.name,
+                path.display()
+            );
+        }
+    }
+}
+
 #[cfg(feature = "armor")]
 impl armor::StrictArmor for TypeLib {
     type Id = crate::TypeLibId;
@@ -187,3 +361,97 @@ impl armor::StrictArmor for TypeLib {
         headers
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl, LIB_ID_STD};
+
+    /// Temporary directory, which is removed on drop, including the case of a failed test.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) { let _ = std::fs::remove_dir_all(&self.0); }
+    }
+
+    /// Writer recording the length of each of the writes instead of the data.
+    #[derive(Default)]
+    struct WriteLens(Vec<usize>);
+
+    impl fmt::Write for WriteLens {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.push(s.len());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_to() {
+        let lib = strict_types_stl();
+        let text = lib.to_string();
+        let mut lens = WriteLens::default();
+        lib.write_to(&mut lens).unwrap();
+        assert_eq!(lens.0.iter().sum::<usize>(), text.len());
+        // The library is streamed in pieces no longer than a single line of the text
+        let longest_line = text.lines().map(str::len).max().unwrap();
+        assert!(lens.0.len() > lib.types.len());
+        assert!(lens.0.iter().all(|len| *len <= longest_line), "{:?}", lens.0);
+    }
+
+    #[test]
+    fn versioned_serialization() {
+        let lib = strict_types_stl();
+        let mut data = lib.to_versioned_serialized().unwrap();
+        assert_eq!(data[0], CURRENT_FORMAT_VERSION);
+        assert_eq!(TypeLib::from_versioned_serialized(&data).unwrap(), lib);
+        data[0] = 0xFF;
+        assert_eq!(
+            TypeLib::from_versioned_serialized(&data).unwrap_err(),
+            LibDeserializeError::UnsupportedVersion(0xFF)
+        );
+    }
+
+    #[test]
+    fn store_load() {
+        let tmp = TempDir::new("strict-types-store");
+        let dir = tmp.0.as_path();
+
+        let std = std_stl();
+        let lib = strict_types_stl();
+        let id = lib.store(dir).unwrap();
+        assert_eq!(id, lib.id());
+        assert_eq!(TypeLib::load(dir, id).unwrap(), lib);
+
+        let std_id = std.store(dir).unwrap();
+        std::fs::rename(TypeLib::store_path(dir, std_id), TypeLib::store_path(dir, id)).unwrap();
+        assert!(matches!(
+            TypeLib::load(dir, id).unwrap_err(),
+            LoadError::IdMismatch { expected, actual } if expected == id && actual == std_id
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(matches!(TypeLib::load(dir, id).unwrap_err(), LoadError::Io(_)));
+    }
+
+    #[test]
+    fn std_lib_snapshot() {
+        let lib = std_stl();
+        let snapshot = lib.to_snapshot_string();
+        assert_eq!(snapshot, std_stl().to_snapshot_string());
+        assert!(snapshot.contains(LIB_ID_STD));
+        for (name, ty) in &lib.types {
+            assert!(snapshot.contains(&format!("data {name} {}", ty.sem_id_named(name))));
+        }
+        lib.assert_snapshot(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/std.snapshot"));
+    }
+}
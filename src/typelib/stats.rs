@@ -0,0 +1,124 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Statistics on the composition of type libraries.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use strict_encoding::Primitive;
+
+use crate::typelib::LibSubref;
+use crate::{Ty, TypeLib};
+
+/// Number of type definitions of each class found in a library, including inline types nested
+/// inside the named ones.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TypeLibStats {
+    pub structs: usize,
+    pub tuples: usize,
+    pub unions: usize,
+    pub enums: usize,
+    pub arrays: usize,
+    pub lists: usize,
+    pub sets: usize,
+    pub maps: usize,
+    pub unicode_chars: usize,
+    /// Number of times each primitive type is used.
+    pub primitives: BTreeMap<Primitive, usize>,
+}
+
+impl TypeLibStats {
+    fn count<Ref: LibSubref>(&mut self, ty: &Ty<Ref>) {
+        match ty {
+            Ty::Primitive(prim) => *self.primitives.entry(*prim).or_default() += 1,
+            Ty::UnicodeChar => self.unicode_chars += 1,
+            Ty::Enum(_) => self.enums += 1,
+            Ty::Union(_) => self.unions += 1,
+            Ty::Tuple(_) => self.tuples += 1,
+            Ty::Struct(_) => self.structs += 1,
+            Ty::Array(..) => self.arrays += 1,
+            Ty::List(..) => self.lists += 1,
+            Ty::Set(..) => self.sets += 1,
+            Ty::Map(..) => self.maps += 1,
+        }
+        for (r, _) in ty.type_refs() {
+            if let Some(inline) = r.as_inline() {
+                self.count(inline);
+            }
+        }
+    }
+}
+
+impl Display for TypeLibStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let classes = [
+            ("structs", self.structs),
+            ("tuples", self.tuples),
+            ("unions", self.unions),
+            ("enums", self.enums),
+            ("arrays", self.arrays),
+            ("lists", self.lists),
+            ("sets", self.sets),
+            ("maps", self.maps),
+            ("unicode chars", self.unicode_chars),
+        ];
+        for (class, count) in classes {
+            writeln!(f, "{class:<16}{count}")?;
+        }
+        writeln!(f, "primitives:")?;
+        for (prim, count) in &self.primitives {
+            writeln!(f, "  {:<14}{count}", prim.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl TypeLib {
+    /// Collects statistics on the library type definitions. Types from the library
+    /// dependencies are not taken into account.
+    pub fn stats(&self) -> TypeLibStats {
+        let mut stats = TypeLibStats::default();
+        for ty in self.types.values() {
+            stats.count(ty);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stl::{std_stl, strict_types_stl};
+
+    #[test]
+    fn stats() {
+        let std = std_stl();
+        let stats = std.stats();
+        assert_eq!(stats.enums, std.types.len());
+        assert_eq!(stats.structs + stats.unions + stats.maps, 0);
+        assert!(stats.primitives.is_empty());
+
+        let stats = strict_types_stl().stats();
+        assert!(stats.structs > 0);
+        assert!(stats.to_string().starts_with(&format!("structs         {}\n", stats.structs)));
+    }
+}
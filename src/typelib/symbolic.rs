@@ -27,12 +27,12 @@ use amplify::confinement::{Confined, SmallOrdMap, TinyOrdMap, TinyOrdSet};
 use amplify::ByteArray;
 use encoding::{LibName, LIB_EMBEDDED};
 use sha2::Digest;
-use strict_encoding::{StrictDumb, TypeName, STRICT_TYPES_LIB};
+use strict_encoding::{FieldName, StrictDumb, TypeName, STRICT_TYPES_LIB};
 
 use super::{LibBuilder, SymbolContext};
-use crate::ast::{PrimitiveRef, SemCommit, SEM_ID_TAG};
+use crate::ast::{Path, PrimitiveRef, SemCommit, SEM_ID_TAG};
 use crate::typelib::{CompileError, ExternRef, NestedContext, SymbolError, TypeIndex, TypeMap};
-use crate::{Dependency, LibRef, SemId, Translate, Ty, TypeLib, TypeLibId, TypeRef};
+use crate::{tagged_hasher, Dependency, LibRef, SemId, Translate, Ty, TypeLib, TypeLibId, TypeRef};
 
 pub type ExternTypes = TinyOrdMap<LibName, SmallOrdMap<SemId, TypeName>>;
 
@@ -110,10 +110,7 @@ impl TranspileRef {
         if let TranspileRef::Extern(r) = self {
             r.sem_id
         } else {
-            let tag = sha2::Sha256::new_with_prefix(SEM_ID_TAG).finalize();
-            let mut hasher = sha2::Sha256::new();
-            hasher.update(tag);
-            hasher.update(tag);
+            let mut hasher = tagged_hasher(SEM_ID_TAG);
             self.sem_commit(&mut hasher);
             SemId::from_byte_array(hasher.finalize())
         }
@@ -140,6 +137,12 @@ impl TypeRef for TranspileRef {
             _ => false,
         }
     }
+    fn is_unit(&self) -> bool {
+        match self {
+            TranspileRef::Embedded(ty) => **ty == Ty::UNIT,
+            _ => false,
+        }
+    }
     fn is_unicode_char(&self) -> bool {
         match self {
             TranspileRef::Embedded(ty) => ty.is_unicode_char(),
@@ -149,6 +152,7 @@ impl TypeRef for TranspileRef {
 }
 
 impl PrimitiveRef for TranspileRef {
+    fn unit() -> Self { TranspileRef::unit() }
     fn byte() -> Self { TranspileRef::Embedded(Box::new(Ty::BYTE)) }
     fn unicode_char() -> Self { TranspileRef::Embedded(Box::new(Ty::UNICODE)) }
 }
@@ -166,10 +170,11 @@ impl Display for TranspileRef {
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(doc_comments)]
 pub enum TranspileError {
-    /// type `{unknown}` referenced inside `{within}` is not known.
+    /// type `{unknown}` referenced inside `{within}{path}` is not known.
     UnknownType {
         unknown: TypeName,
-        within: Ty<TranspileRef>,
+        within: TypeName,
+        path: Path,
     },
 
     /// unknown library `{0}` absent from dependencies.
@@ -183,24 +188,68 @@ pub enum TranspileError {
 
     /// library `{0}` contains too many types.
     LibTooLarge(LibName),
+
+    /// type alias `{0}` collides with the name of another type.
+    AliasCollision(TypeName),
+
+    /// type name `{0}` is used by two different types.
+    NameCollision(TypeName),
+
+    /// type `{0}` differs from the type with the same name in the imported library.
+    ImportMismatch(TypeName),
+
+    /// type `{name}` contains no data, which happens when a generic type is transpiled with
+    /// placeholder parameters like `()`.
+    DegenerateType { name: TypeName },
+
+    /// Rust type `{type_name}` can't be represented as a strict type: {reason}.
+    Unsupported {
+        type_name: String,
+        reason: &'static str,
+    },
 }
 
 impl LibBuilder {
     pub fn compile_symbols(self) -> Result<SymbolicLib, TranspileError> {
-        let (name, known_libs, extern_types, types) =
-            (self.lib_name, self.known_libs, self.extern_types, self.types);
-
-        for ty in types.values() {
-            for (subty, _) in ty.type_refs() {
-                if let TranspileRef::Named(name) = subty {
-                    if !types.contains_key(name) {
-                        return Err(TranspileError::UnknownType {
-                            unknown: name.clone(),
-                            within: ty.clone(),
-                        });
-                    }
+        if let Some(name) = self.name_collision {
+            return Err(TranspileError::NameCollision(name));
+        }
+        if let Some(name) = self.import_mismatch {
+            return Err(TranspileError::ImportMismatch(name));
+        }
+        if let Some((type_name, reason)) = self.unsupported {
+            return Err(TranspileError::Unsupported { type_name, reason });
+        }
+        if let Some(name) = self.degenerate {
+            return Err(TranspileError::DegenerateType { name });
+        }
+        let (name, known_libs, extern_types, mut types, aliases) =
+            (self.lib_name, self.known_libs, self.extern_types, self.types, self.aliases);
+
+        for (within, ty) in &types {
+            let mut path = Path::new();
+            if let Some(unknown) =
+                find_unknown_type(ty, &|name| types.contains_key(name), &mut path)
+            {
+                return Err(TranspileError::UnknownType {
+                    unknown,
+                    within: within.clone(),
+                    path,
+                });
+            }
+        }
+
+        if !aliases.is_empty() {
+            let mut renamed = BTreeMap::new();
+            for (name, ty) in types {
+                let name = aliases.get(&name).cloned().unwrap_or(name);
+                if renamed.contains_key(&name) {
+                    return Err(TranspileError::AliasCollision(name));
                 }
+                let ty = ty.translate(&mut (), &aliases).unwrap_or_else(|never| match never {});
+                renamed.insert(name, ty);
             }
+            types = renamed;
         }
 
         let mut used_dependencies = BTreeSet::<Dependency>::new();
@@ -241,7 +290,27 @@ impl LibBuilder {
         })
     }
 
-    pub fn compile(self) -> Result<TypeLib, CompileError> { self.compile_symbols()?.compile() }
+    pub fn compile(self) -> Result<TypeLib, CompileError> {
+        let forbid_floats = self.forbid_floats;
+        let deny_unused = self.deny_unused;
+        let roots = self
+            .roots
+            .iter()
+            .map(|name| self.aliases.get(name).unwrap_or(name).clone())
+            .collect::<BTreeSet<_>>();
+        let lib = self.compile_symbols()?.compile()?;
+        if forbid_floats {
+            if let Some(path) = lib.float_path() {
+                return Err(CompileError::FloatForbidden { path });
+            }
+        }
+        if deny_unused {
+            if let Some(name) = lib.unused_types(&roots).into_iter().next() {
+                return Err(CompileError::UnusedType(name.clone()));
+            }
+        }
+        Ok(lib)
+    }
 }
 
 impl SymbolicLib {
@@ -254,6 +323,17 @@ impl SymbolicLib {
         let mut new_types = BTreeMap::<TypeName, Ty<LibRef>>::new();
         let names = old_types.keys().cloned().collect::<BTreeSet<_>>();
 
+        for (name, ty) in &old_types {
+            let mut path = Path::new();
+            if let Some(field) = find_duplicate_name(ty, &mut path) {
+                return Err(CompileError::DuplicateFieldName {
+                    ty: name.clone(),
+                    path,
+                    name: field,
+                });
+            }
+        }
+
         while !old_types.is_empty() {
             let mut found = false;
             for name in &names {
@@ -314,6 +394,55 @@ impl SymbolicLib {
     }
 }
 
+fn push_step(ty: &Ty<TranspileRef>, pos: usize, path: &mut Path) {
+    let step = ty.step_at(pos as u8).expect("type reference without path step");
+    path.push(step).expect("type nesting exceeds path length limit");
+}
+
+/// Finds the first reference to a type by a name which is not known, leaving in `path` the
+/// location of the reference.
+fn find_unknown_type(
+    ty: &Ty<TranspileRef>,
+    is_known: &impl Fn(&TypeName) -> bool,
+    path: &mut Path,
+) -> Option<TypeName> {
+    for (pos, (r, _)) in ty.type_refs().enumerate() {
+        push_step(ty, pos, path);
+        match r {
+            TranspileRef::Named(name) if !is_known(name) => return Some(name.clone()),
+            TranspileRef::Embedded(inner) => {
+                if let Some(name) = find_unknown_type(inner, is_known, path) {
+                    return Some(name);
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+    None
+}
+
+/// Finds the first field or variant name repeated within the same type, leaving in `path` the
+/// location of the type containing the duplicate.
+fn find_duplicate_name(ty: &Ty<TranspileRef>, path: &mut Path) -> Option<FieldName> {
+    let mut set = BTreeSet::new();
+    let names = ty.field_names().unwrap_or_default();
+    if let Some(name) = names.into_iter().flatten().find(|name| !set.insert(name.clone())) {
+        return Some(name);
+    }
+    for (pos, (r, _)) in ty.type_refs().enumerate() {
+        let Some(inner) = r.as_ty() else {
+            continue;
+        };
+        push_step(ty, pos, path);
+        if let Some(name) = find_duplicate_name(inner, path) {
+            return Some(name);
+        }
+        path.pop();
+    }
+    None
+}
+
 impl TypeLib {
     pub fn to_symbolic(&self) -> Result<SymbolicLib, SymbolError> {
         let lib_index = self.dependencies.iter().map(|dep| (dep.id, dep.name.clone())).collect();
@@ -341,3 +470,203 @@ impl TypeLib {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::Sizing;
+
+    use super::*;
+    use crate::ast::{field, NamedFields};
+
+    fn bar(baz: Ty<TranspileRef>) -> LibBuilder {
+        let fields = NamedFields::try_from(vec![
+            field("id", TranspileRef::from(Ty::U8)),
+            field("baz", TranspileRef::from(baz)),
+        ])
+        .unwrap();
+        let mut builder = LibBuilder::new(libname!("TestLib"), None);
+        builder.types.insert(tn!("Bar"), Ty::struc(fields));
+        builder
+    }
+
+    #[test]
+    fn error_paths() {
+        let unknown = Ty::list(TranspileRef::Named(tn!("Foo")), Sizing::U8);
+        assert_eq!(
+            bar(unknown).compile_symbols().unwrap_err().to_string(),
+            "type `Foo` referenced inside `Bar.baz[]` is not known."
+        );
+
+        let dup = NamedFields::try_from(vec![
+            field("foo", TranspileRef::from(Ty::U8)),
+            field("foo", TranspileRef::from(Ty::U16)),
+        ])
+        .unwrap();
+        assert_eq!(
+            bar(Ty::struc(dup)).compile().unwrap_err().to_string(),
+            "type `Bar.baz` contains several fields or variants named `foo`."
+        );
+    }
+
+    mod v1 {
+        #[derive(Clone, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib")]
+        pub struct Foo(pub u8);
+
+        #[derive(Clone, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib")]
+        pub struct Holder(pub Foo);
+    }
+
+    mod v2 {
+        #[derive(Clone, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib")]
+        pub struct Foo(pub u16);
+
+        #[derive(Clone, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib")]
+        pub struct Holder(pub Foo);
+    }
+
+    #[test]
+    fn name_collision() {
+        let builder =
+            LibBuilder::new(libname!("TestLib"), None).try_transpile::<v1::Foo>().unwrap();
+        assert_eq!(builder.transpiled_names().collect::<Vec<_>>(), [&tn!("Foo")]);
+        let builder = builder.try_transpile::<v1::Foo>().unwrap();
+        assert_eq!(
+            builder.try_transpile::<v2::Foo>().unwrap_err(),
+            TranspileError::NameCollision(tn!("Foo"))
+        );
+
+        let builder = LibBuilder::new(libname!("TestLib"), None)
+            .transpile::<v1::Foo>()
+            .transpile::<v2::Foo>();
+        assert_eq!(builder.compile().unwrap_err(), CompileError::DuplicateName(tn!("Foo")));
+    }
+
+    #[test]
+    fn import_mismatch() {
+        let base =
+            LibBuilder::new(libname!("TestLib"), None).transpile::<v1::Foo>().compile().unwrap();
+        let builder =
+            || LibBuilder::new(libname!("TestLib"), None).import(&base, libname!("BaseLib"));
+
+        let lib = builder().transpile::<v1::Holder>().compile().unwrap();
+        assert!(!lib.types.contains_key(&tn!("Foo")));
+        assert!(lib.extern_types.contains_key(&libname!("BaseLib")));
+
+        assert_eq!(
+            builder().transpile::<v2::Holder>().compile().unwrap_err(),
+            CompileError::ImportMismatch(tn!("Foo"))
+        );
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Local(u8);
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "OtherLib")]
+    struct Other(Local);
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Root(Other, u16);
+
+    #[test]
+    fn unused_types() {
+        let other = Dependency::with(TypeLibId::from([0u8; 32]), libname!("OtherLib"));
+        let builder = || LibBuilder::new(libname!("TestLib"), [other.clone()]).transpile::<Root>();
+        let lib = builder().compile().unwrap();
+        assert!(lib.types.contains_key(&tn!("Local")));
+        assert_eq!(lib.unused_types(&bset![tn!("Root")]).into_iter().collect::<Vec<_>>(), [&tn!(
+            "Local"
+        )]);
+        assert_eq!(lib.unused_types(&bset![tn!("Root"), tn!("Local")]).len(), 0);
+
+        assert_eq!(
+            builder().deny_unused().compile().unwrap_err(),
+            CompileError::UnusedType(tn!("Local"))
+        );
+        assert!(builder().transpile::<Local>().deny_unused().compile().is_ok());
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Wrapper<T: StrictDumb + encoding::StrictEncode + encoding::StrictDecode>(T);
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Tagged {
+        id: u8,
+        tag: (),
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Void {}
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Units((), ());
+
+    #[test]
+    fn degenerate_types() {
+        assert_eq!(
+            LibBuilder::new(libname!("TestLib"), None)
+                .transpile::<Wrapper<()>>()
+                .compile()
+                .unwrap_err(),
+            CompileError::DegenerateType {
+                name: tn!("Wrapper")
+            }
+        );
+        let lib =
+            LibBuilder::new(libname!("TestLib"), None).transpile::<Tagged>().compile().unwrap();
+        assert_eq!(lib.types.get(&tn!("Tagged")).unwrap().to_string(), "id U8, tag ()");
+
+        let lib = LibBuilder::new(libname!("TestLib"), None)
+            .transpile::<Void>()
+            .transpile::<Units>()
+            .transpile::<Wrapper<u8>>()
+            .compile()
+            .unwrap();
+        assert_eq!(lib.types.get(&tn!("Void")), Some(&Ty::UNIT));
+        assert_eq!(lib.types.get(&tn!("Units")).unwrap().to_string(), "(), ()");
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Marked {
+        id: u8,
+        marker: std::marker::PhantomData<u64>,
+    }
+
+    #[test]
+    fn unsupported_types() {
+        let err =
+            LibBuilder::new(libname!("TestLib"), None).transpile::<Marked>().compile().unwrap_err();
+        assert_eq!(err, CompileError::Unsupported {
+            type_name: s!("core::marker::PhantomData<u64>"),
+            reason: "it doesn't belong to a strict type library"
+        });
+        assert_eq!(
+            err.to_string(),
+            "Rust type `core::marker::PhantomData<u64>` can't be represented as a strict type: it \
+             doesn't belong to a strict type library."
+        );
+    }
+}
@@ -21,8 +21,11 @@
 // limitations under the License.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
 use std::io;
 use std::io::Sink;
+use std::marker::PhantomData;
+use std::str::FromStr;
 
 use amplify::confinement::{Confined, U64 as U64MAX};
 use amplify::Wrapper;
@@ -35,7 +38,9 @@ use strict_encoding::{
 };
 
 use crate::ast::{EnumVariants, Field, NamedFields, UnionVariants, UnnamedFields};
-use crate::{Dependency, SemId, SymbolRef, TranspileRef, Ty, TypeLibId};
+use crate::{
+    Dependency, SemId, SymbolRef, Translate, TranspileError, TranspileRef, Ty, TypeLib, TypeLibId,
+};
 
 const MAX_WRITE_COUNT: usize = U64MAX;
 
@@ -48,12 +53,48 @@ pub trait BuilderParent: StrictParent<StreamWriter<Sink>> {
     fn report_compiled(self, lib: LibName, name: Option<TypeName>, ty: Ty<TranspileRef>) -> Self;
 }
 
+/// Strict type (or a set of types) which can be added to a library with
+/// [`LibBuilder::transpile_registered`], allowing to select library types at runtime instead of
+/// chaining [`LibBuilder::transpile`] calls.
+pub trait StrictTypeRegistry {
+    /// Transpiles the registered types into the provided builder.
+    fn register(&self, builder: LibBuilder) -> LibBuilder;
+}
+
+/// Registry entry transpiling a single strict type `T`.
+#[derive(Debug)]
+pub struct Registered<T: StrictEncode + StrictDumb>(PhantomData<T>);
+
+impl<T: StrictEncode + StrictDumb> Default for Registered<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: StrictEncode + StrictDumb> Registered<T> {
+    pub const fn new() -> Self { Registered(PhantomData) }
+}
+
+impl<T: StrictEncode + StrictDumb> StrictTypeRegistry for Registered<T> {
+    fn register(&self, builder: LibBuilder) -> LibBuilder { builder.transpile::<T>() }
+}
+
 #[derive(Debug)]
 pub struct LibBuilder {
     pub(super) lib_name: LibName,
     pub(super) known_libs: BTreeSet<Dependency>,
     pub(super) extern_types: BTreeMap<LibName, BTreeMap<SemId, TypeName>>,
     pub(super) types: BTreeMap<TypeName, Ty<TranspileRef>>,
+    imported: BTreeMap<TypeName, SymbolRef>,
+    pub(super) aliases: BTreeMap<TypeName, TypeName>,
+    pub(super) forbid_floats: bool,
+    pub(super) name_collision: Option<TypeName>,
+    pub(super) import_mismatch: Option<TypeName>,
+    pub(super) degenerate: Option<TypeName>,
+    /// Whether each of the Rust types being transpiled (innermost last) is an instantiation of
+    /// a generic type.
+    generic: Vec<bool>,
+    pub(super) unsupported: Option<(String, &'static str)>,
+    pub(super) roots: BTreeSet<TypeName>,
+    pub(super) deny_unused: bool,
     sink: StreamWriter<Sink>,
     last_compiled: Option<TranspileRef>,
 }
@@ -68,13 +109,133 @@ impl LibBuilder {
             known_libs: known_libs.into_iter().collect(),
             extern_types: empty!(),
             types: empty!(),
+            imported: empty!(),
+            aliases: empty!(),
+            forbid_floats: false,
+            name_collision: None,
+            import_mismatch: None,
+            degenerate: None,
+            generic: empty!(),
+            unsupported: None,
+            roots: empty!(),
+            deny_unused: false,
             sink: StreamWriter::sink::<MAX_WRITE_COUNT>(),
             last_compiled: None,
         }
     }
 
-    pub fn transpile<T: StrictEncode + StrictDumb>(self) -> Self {
-        T::strict_dumb().strict_encode(self).expect("memory encoding doesn't error")
+    /// Transpiles type `T` and all types it depends on into the library.
+    ///
+    /// If the type (or one of its dependencies) has the same name as a different type which was
+    /// already transpiled, the library compilation fails with
+    /// [`TranspileError::NameCollision`]; use [`LibBuilder::try_transpile`] to detect the
+    /// collision at the call introducing it.
+    ///
+    /// [`TranspileError::NameCollision`]: crate::TranspileError::NameCollision
+    pub fn transpile<T: StrictEncode + StrictDumb>(mut self) -> Self {
+        if let Some(name) =
+            T::strict_name().filter(|_| T::STRICT_LIB_NAME == self.lib_name.as_str())
+        {
+            self.roots.insert(name);
+        }
+        self.generic.push(is_generic::<T>());
+        let mut builder =
+            T::strict_dumb().strict_encode(self).expect("memory encoding doesn't error");
+        builder.generic.pop();
+        builder
+    }
+
+    /// Transpiles type `T` like [`LibBuilder::transpile`], failing if the type or one of its
+    /// dependencies has the same name as a different type which was already transpiled.
+    pub fn try_transpile<T: StrictEncode + StrictDumb>(self) -> Result<Self, TranspileError> {
+        let collided = self.name_collision.is_some();
+        let builder = self.transpile::<T>();
+        match &builder.name_collision {
+            Some(name) if !collided => Err(TranspileError::NameCollision(name.clone())),
+            _ => Ok(builder),
+        }
+    }
+
+    /// Returns names of the types transpiled into the library so far, not counting types
+    /// imported from other libraries.
+    pub fn transpiled_names(&self) -> impl Iterator<Item = &TypeName> { self.types.keys() }
+
+    /// Transpiles all types from the provided registry entries, in the order of the entries.
+    pub fn transpile_registered(self, registry: &[&dyn StrictTypeRegistry]) -> Self {
+        registry.iter().fold(self, |builder, entry| entry.register(builder))
+    }
+
+    /// Registers types defined by an already compiled library, such that the
+    /// types with the same names transpiled by the builder are referenced as
+    /// external types from that library (under the provided `alias`) instead
+    /// of being defined once again.
+    ///
+    /// If a transpiled type has the name of an imported type, but a different
+    /// definition, the library compilation fails with
+    /// [`TranspileError::ImportMismatch`].
+    ///
+    /// [`TranspileError::ImportMismatch`]: crate::TranspileError::ImportMismatch
+    pub fn import(mut self, lib: &TypeLib, alias: impl Into<LibName>) -> Self {
+        let alias = alias.into();
+        let lib_id = lib.id();
+        for (name, ty) in &lib.types {
+            let sem_id = ty.sem_id_named(name);
+            self.imported
+                .insert(name.clone(), SymbolRef::with(alias.clone(), name.clone(), lib_id, sem_id));
+        }
+        self.known_libs.insert(Dependency::with(lib_id, alias));
+        self
+    }
+
+    /// Exports type under a name different from the one it has in the Rust code. The renaming is
+    /// applied during the library compilation both to the type definition and to all references
+    /// to the type inside the library.
+    ///
+    /// Aliases for the types which are not part of the library are ignored; aliases colliding
+    /// with other type names result in [`TranspileError::AliasCollision`] during compilation.
+    ///
+    /// [`TranspileError::AliasCollision`]: crate::TranspileError::AliasCollision
+    pub fn alias_type(
+        mut self,
+        rust_name: impl Into<TypeName>,
+        exported_name: impl Into<TypeName>,
+    ) -> Self {
+        self.aliases.insert(rust_name.into(), exported_name.into());
+        self
+    }
+
+    /// Makes [`LibBuilder::compile`] fail with [`CompileError::FloatForbidden`] if any of the
+    /// library types contains a floating-point number, which is required for the contexts
+    /// demanding deterministic encoding and comparison of the values.
+    ///
+    /// [`CompileError::FloatForbidden`]: crate::CompileError::FloatForbidden
+    pub fn forbid_floats(mut self) -> Self {
+        self.forbid_floats = true;
+        self
+    }
+
+    /// Makes [`LibBuilder::compile`] fail with [`CompileError::UnusedType`] if the library
+    /// contains types which were neither transpiled explicitly nor are used, directly or
+    /// transitively, by the explicitly transpiled types. Such types may appear, for instance,
+    /// when a type from another library has fields of types from this library.
+    ///
+    /// [`CompileError::UnusedType`]: crate::CompileError::UnusedType
+    pub fn deny_unused(mut self) -> Self {
+        self.deny_unused = true;
+        self
+    }
+
+    /// Records the first Rust type which can't be represented as a strict type, returning a
+    /// placeholder for it; the library compilation will fail.
+    fn unsupported<T>(mut self, reason: &'static str) -> (Self, TranspileRef) {
+        self.unsupported.get_or_insert_with(|| (std::any::type_name::<T>().to_owned(), reason));
+        (self, TranspileRef::unit())
+    }
+
+    fn import_ref(&mut self, name: &TypeName) -> Option<TranspileRef> {
+        let r = self.imported.get(name)?.clone();
+        self.extern_types.entry(r.lib_name.clone()).or_default().insert(r.sem_id, name.clone());
+        Some(TranspileRef::Extern(r))
     }
 
     fn dependency_id(&self, lib_name: &LibName) -> TypeLibId {
@@ -211,11 +372,16 @@ impl StrictParent<StreamWriter<Sink>> for LibBuilder {
 impl BuilderParent for LibBuilder {
     fn compile_type<T: StrictEncode>(self, value: &T) -> (Self, TranspileRef) {
         let _compile = |mut me: Self| -> (Self, TranspileRef) {
+            me.generic.push(is_generic::<T>());
             me = value.strict_encode(me).expect("too many types in the library");
+            me.generic.pop();
             let r =
                 me.last_compiled.clone().expect("no type found after strict encoding procedure");
             (me, r)
         };
+        if LibName::from_str(T::STRICT_LIB_NAME).is_err() {
+            return self.unsupported::<T>("it doesn't belong to a strict type library");
+        }
         match (T::STRICT_LIB_NAME, T::strict_name()) {
             (LIB_EMBEDDED, _) | (_, None) => _compile(self),
             (lib, Some(name)) if lib != self.lib_name.as_str() => {
@@ -236,14 +402,26 @@ impl BuilderParent for LibBuilder {
         ty: Ty<TranspileRef>,
     ) -> Self {
         let r = match (lib, name) {
+            (lib, Some(name)) if lib == self.lib_name && self.imported.contains_key(&name) => {
+                let r = self.import_ref(&name).expect("imported type");
+                if r.id() != ty.sem_id_named(&name) {
+                    self.import_mismatch.get_or_insert_with(|| name.clone());
+                }
+                r
+            }
             (lib, Some(name)) if lib == self.lib_name => {
-                if let Some(old_ty) = self.types.get(&name) {
-                    assert_eq!(
-                        old_ty, &ty,
-                        "repeated type name '{name}' for two different types '{old_ty}' and '{ty}'",
-                    );
+                match self.types.get(&name) {
+                    Some(old_ty) if old_ty != &ty => {
+                        self.name_collision.get_or_insert_with(|| name.clone());
+                    }
+                    Some(_) => {}
+                    None => {
+                        if self.generic.last() == Some(&true) && is_degenerate(&ty) {
+                            self.degenerate.get_or_insert_with(|| name.clone());
+                        }
+                        self.types.insert(name.clone(), ty);
+                    }
                 }
-                self.types.insert(name.clone(), ty);
                 TranspileRef::Named(name)
             }
             (lib, Some(name)) => {
@@ -259,6 +437,21 @@ impl BuilderParent for LibBuilder {
     }
 }
 
+/// Detects whether Rust type is an instantiation of a generic type, like `Wrapper<()>`.
+fn is_generic<T>() -> bool { std::any::type_name::<T>().contains('<') }
+
+/// Detects structures and tuples which have fields, but all of them are units. For an
+/// instantiation of a generic type this means that the type parameters were replaced with
+/// placeholders.
+fn is_degenerate(ty: &Ty<TranspileRef>) -> bool {
+    let unit = TranspileRef::unit();
+    match ty {
+        Ty::Struct(fields) => fields.iter().all(|field| field.ty == unit),
+        Ty::Tuple(fields) => fields.iter().all(|field| *field == unit),
+        _ => false,
+    }
+}
+
 #[derive(Debug)]
 pub struct StructBuilder<P: BuilderParent> {
     lib: LibName,
@@ -691,3 +884,76 @@ impl WriteUnion for UnionBuilder {
         self._complete_write(ty)
     }
 }
+
+impl Translate<TranspileRef> for TranspileRef {
+    type Context = BTreeMap<TypeName, TypeName>;
+    type Builder = ();
+    type Error = Infallible;
+
+    fn translate(
+        self,
+        builder: &mut Self::Builder,
+        aliases: &Self::Context,
+    ) -> Result<TranspileRef, Self::Error> {
+        Ok(match self {
+            TranspileRef::Embedded(ty) => {
+                TranspileRef::Embedded(Box::new(ty.translate(builder, aliases)?))
+            }
+            TranspileRef::Named(name) => {
+                TranspileRef::Named(aliases.get(&name).cloned().unwrap_or(name))
+            }
+            TranspileRef::Extern(ext) => TranspileRef::Extern(ext),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::STRICT_TYPES_LIB;
+
+    use super::*;
+    use crate::stl::std_stl;
+    use crate::{CompileError, TypeSysId};
+
+    #[test]
+    fn imported_types() {
+        let std = std_stl();
+        let idents = LibBuilder::new(libname!(STRICT_TYPES_LIB), [std.to_dependency()])
+            .transpile::<TypeName>()
+            .compile()
+            .unwrap();
+        let lib = LibBuilder::new(libname!(STRICT_TYPES_LIB), [std.to_dependency()])
+            .import(&idents, libname!("Idents"))
+            .transpile::<SymbolRef>()
+            .compile()
+            .unwrap();
+        assert!(!lib.types.contains_key(&tn!("TypeName")));
+        assert!(lib.dependencies.contains(&Dependency::with(idents.id(), libname!("Idents"))));
+        assert!(lib.extern_types.contains_key(&libname!("Idents")));
+    }
+
+    #[test]
+    fn registered_types() {
+        let builder = || LibBuilder::new(libname!(STRICT_TYPES_LIB), [std_stl().to_dependency()]);
+        let registry: [&dyn StrictTypeRegistry; 2] =
+            [&Registered::<SymbolRef>::new(), &Registered::<TypeSysId>::new()];
+        assert_eq!(
+            builder().transpile_registered(&registry).compile().unwrap(),
+            builder().transpile::<SymbolRef>().transpile::<TypeSysId>().compile().unwrap()
+        );
+    }
+
+    #[test]
+    fn type_aliases() {
+        let builder = || {
+            LibBuilder::new(libname!(STRICT_TYPES_LIB), [std_stl().to_dependency()])
+                .transpile::<SymbolRef>()
+        };
+        let lib = builder().alias_type(tn!("TypeName"), tn!("TyName")).compile().unwrap();
+        assert!(lib.types.contains_key(&tn!("TyName")));
+        assert!(!lib.types.contains_key(&tn!("TypeName")));
+
+        let err = builder().alias_type(tn!("TypeName"), tn!("LibName")).compile().unwrap_err();
+        assert_eq!(err, CompileError::DuplicateName(tn!("LibName")));
+    }
+}
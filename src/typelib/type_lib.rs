@@ -21,24 +21,417 @@
 // limitations under the License.
 
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
 
 use amplify::confinement::{Confined, TinyOrdSet};
+use amplify::num::u24;
 use baid64::DisplayBaid64;
 use encoding::StrictDumb;
-use strict_encoding::{LibName, TypeName, STRICT_TYPES_LIB};
+use strict_encoding::{LibName, NumCls, Sizing, TypeName, STRICT_TYPES_LIB};
 
+use crate::ast::Path;
 use crate::typelib::compile::CompileError;
 use crate::typelib::id::TypeLibId;
 use crate::typelib::ExternTypes;
+use crate::typesys::TypeFqn;
 use crate::{SemId, Ty, TypeRef};
 
-pub trait LibSubref: TypeRef {}
-impl LibSubref for LibRef {}
-impl LibSubref for InlineRef {}
-impl LibSubref for InlineRef1 {}
-impl LibSubref for InlineRef2 {}
+type LibResolver<'a, 'lib> = dyn Fn(SemId) -> Option<&'lib Ty<LibRef>> + 'a;
+
+/// Type references used inside type libraries at different levels of type nesting.
+pub trait LibSubref: TypeRef + Display {
+    /// Reference type used by the inline types at the next nesting level.
+    type Inline: LibSubref;
+
+    fn as_inline(&self) -> Option<&Ty<Self::Inline>>;
+    fn as_sem_id(&self) -> Option<SemId>;
+    fn as_extern(&self) -> Option<&ExternRef>;
+
+    fn is_inline(&self) -> bool { self.as_inline().is_some() }
+    fn is_named(&self) -> bool { self.as_sem_id().is_some() }
+    fn is_extern(&self) -> bool { self.as_extern().is_some() }
+
+    /// Returns semantic id of the type referenced by name, either from the same library or from
+    /// an external one.
+    fn referenced_id(&self) -> Option<SemId> {
+        self.as_sem_id().or_else(|| self.as_extern().map(|ext| ext.sem_id))
+    }
+
+    /// Returns semantic id of the type behind the reference. For inline types the id is computed
+    /// from the type itself, matching the id the type receives when the library is linked into
+    /// a type system.
+    fn id(&self) -> SemId {
+        match self.as_inline() {
+            Some(ty) => ty.sem_id_unnamed(),
+            None => {
+                self.referenced_id().expect("library type reference is neither inline nor named")
+            }
+        }
+    }
+
+    /// Returns short human-readable description of the reference kind and its target, like
+    /// `inline struct with 3 fields`, `named type semid:xjPyRhwn…` or `extern type
+    /// semid:xjPyRhwn… from library ralph-blue-lucky`. Intended for diagnostics.
+    fn about(&self) -> String {
+        if let Some(ty) = self.as_inline() {
+            let cls = ty.cls();
+            return match ty {
+                Ty::Struct(fields) => format!("inline {cls} with {} fields", fields.len()),
+                Ty::Tuple(fields) => format!("inline {cls} with {} fields", fields.len()),
+                Ty::Union(variants) => format!("inline {cls} with {} variants", variants.len()),
+                Ty::Enum(variants) => format!("inline {cls} with {} variants", variants.len()),
+                Ty::Primitive(prim) => format!("inline {cls} {prim}"),
+                _ => format!("inline {cls}"),
+            };
+        }
+        if let Some(ext) = self.as_extern() {
+            return format!(
+                "extern type {} from library {}",
+                ext.sem_id.to_short_string(),
+                ext.lib_id.to_baid64_mnemonic()
+            );
+        }
+        match self.as_sem_id() {
+            Some(sem_id) => format!("named type {}", sem_id.to_short_string()),
+            None => unreachable!("library type reference is neither inline nor named"),
+        }
+    }
+}
+
+impl LibSubref for LibRef {
+    type Inline = InlineRef;
+
+    fn as_inline(&self) -> Option<&Ty<InlineRef>> {
+        match self {
+            LibRef::Inline(ty) => Some(ty),
+            _ => None,
+        }
+    }
+    fn as_sem_id(&self) -> Option<SemId> {
+        match self {
+            LibRef::Named(sem_id) => Some(*sem_id),
+            _ => None,
+        }
+    }
+    fn as_extern(&self) -> Option<&ExternRef> {
+        match self {
+            LibRef::Extern(ext) => Some(ext),
+            _ => None,
+        }
+    }
+}
+
+impl LibSubref for InlineRef {
+    type Inline = InlineRef1;
+
+    fn as_inline(&self) -> Option<&Ty<InlineRef1>> {
+        match self {
+            InlineRef::Inline(ty) => Some(ty),
+            _ => None,
+        }
+    }
+    fn as_sem_id(&self) -> Option<SemId> {
+        match self {
+            InlineRef::Named(sem_id) => Some(*sem_id),
+            _ => None,
+        }
+    }
+    fn as_extern(&self) -> Option<&ExternRef> {
+        match self {
+            InlineRef::Extern(ext) => Some(ext),
+            _ => None,
+        }
+    }
+}
+
+impl LibSubref for InlineRef1 {
+    type Inline = InlineRef2;
+
+    fn as_inline(&self) -> Option<&Ty<InlineRef2>> {
+        match self {
+            InlineRef1::Inline(ty) => Some(ty),
+            _ => None,
+        }
+    }
+    fn as_sem_id(&self) -> Option<SemId> {
+        match self {
+            InlineRef1::Named(sem_id) => Some(*sem_id),
+            _ => None,
+        }
+    }
+    fn as_extern(&self) -> Option<&ExternRef> {
+        match self {
+            InlineRef1::Extern(ext) => Some(ext),
+            _ => None,
+        }
+    }
+}
+
+impl LibSubref for InlineRef2 {
+    type Inline = InlineRef2;
+
+    fn as_inline(&self) -> Option<&Ty<InlineRef2>> { None }
+    fn as_sem_id(&self) -> Option<SemId> {
+        match self {
+            InlineRef2::Named(sem_id) => Some(*sem_id),
+            InlineRef2::Extern(_) => None,
+        }
+    }
+    fn as_extern(&self) -> Option<&ExternRef> {
+        match self {
+            InlineRef2::Extern(ext) => Some(ext),
+            InlineRef2::Named(_) => None,
+        }
+    }
+}
+
+fn is_zero_sized_named<'lib>(
+    sem_id: SemId,
+    resolver: &LibResolver<'_, 'lib>,
+    visited: &mut BTreeSet<SemId>,
+) -> bool {
+    // A type referencing itself can't be zero-sized, since the recursion must
+    // be broken by a collection or a union, which always have non-zero size.
+    if !visited.insert(sem_id) {
+        return false;
+    }
+    let res =
+        resolver(sem_id).map(|ty| ty.is_zero_sized_inner(resolver, visited)).unwrap_or_default();
+    visited.remove(&sem_id);
+    res
+}
+
+/// Size of the length prefix used in the encoding of a collection with the provided limits.
+pub(crate) fn len_prefix(sizing: Sizing) -> u8 {
+    match sizing.max {
+        max if max <= u8::MAX as u64 => 1,
+        max if max <= u16::MAX as u64 => 2,
+        max if max <= u24::MAX.into_u64() => 3,
+        max if max <= u32::MAX as u64 => 4,
+        _ => 8,
+    }
+}
+
+impl<Ref: LibSubref> Ty<Ref> {
+    /// Detects whether the type is always encoded into zero bytes.
+    ///
+    /// Named and external type references are resolved with the provided
+    /// `resolver`; if a type can't be resolved, it is not considered
+    /// zero-sized.
+    pub fn is_zero_sized<'lib>(
+        &self,
+        resolver: impl Fn(SemId) -> Option<&'lib Ty<LibRef>>,
+    ) -> bool {
+        self.is_zero_sized_inner(&resolver, &mut BTreeSet::new())
+    }
+
+    fn is_zero_sized_ref<'lib>(
+        r: &Ref,
+        resolver: &LibResolver<'_, 'lib>,
+        visited: &mut BTreeSet<SemId>,
+    ) -> bool {
+        match (r.as_inline(), r.referenced_id()) {
+            (Some(ty), _) => ty.is_zero_sized_inner(resolver, visited),
+            (None, Some(sem_id)) => is_zero_sized_named(sem_id, resolver, visited),
+            (None, None) => unreachable!("library type reference is neither inline nor named"),
+        }
+    }
+
+    fn is_zero_sized_inner<'lib>(
+        &self,
+        resolver: &LibResolver<'_, 'lib>,
+        visited: &mut BTreeSet<SemId>,
+    ) -> bool {
+        match self {
+            Ty::Primitive(prim) => prim.byte_size() == 0,
+            Ty::Array(_, 0) => true,
+            Ty::Array(ty, _) => Self::is_zero_sized_ref(ty, resolver, visited),
+            Ty::Tuple(fields) => {
+                fields.iter().all(|ty| Self::is_zero_sized_ref(ty, resolver, visited))
+            }
+            Ty::Struct(fields) => {
+                fields.iter().all(|field| Self::is_zero_sized_ref(&field.ty, resolver, visited))
+            }
+            // Enums and unions always encode a tag, collections - their length
+            Ty::UnicodeChar
+            | Ty::Enum(_)
+            | Ty::Union(_)
+            | Ty::List(..)
+            | Ty::Set(..)
+            | Ty::Map(..) => false,
+        }
+    }
+
+    /// Returns exact length of the type encoding, if the type is always encoded into the same
+    /// number of bytes.
+    ///
+    /// Returns `None` if the type (or any of the types it references) contains unicode
+    /// characters, collections which may have different number of items, or unions with
+    /// variants of different length. Named and external type references are resolved with the
+    /// provided `resolver`; if a type can't be resolved, its length is considered unknown.
+    pub fn encoded_len_fixed<'lib>(
+        &self,
+        resolver: impl Fn(SemId) -> Option<&'lib Ty<LibRef>>,
+    ) -> Option<usize> {
+        self.encoded_len_inner(&resolver, &mut BTreeSet::new())
+    }
+
+    /// Detects whether the type is always encoded into the same number of bytes, i.e. whether
+    /// it contains no unicode characters, collections with a variable number of items or unions
+    /// with variants of a different length. See [`Ty::encoded_len_fixed`] for the details.
+    pub fn is_fixed_size<'lib>(
+        &self,
+        resolver: impl Fn(SemId) -> Option<&'lib Ty<LibRef>>,
+    ) -> bool {
+        self.encoded_len_fixed(resolver).is_some()
+    }
+
+    /// Returns byte offsets of the structure or tuple fields within the type encoding.
+    ///
+    /// Since strict encoding doesn't use padding, the offsets are the sums of the encoded
+    /// lengths of the preceding fields. Returns `None` if the type is neither a structure nor a
+    /// tuple, or if any of its fields doesn't have a fixed encoded length (see
+    /// [`Ty::encoded_len_fixed`]).
+    pub fn field_offsets<'lib>(
+        &self,
+        resolver: impl Fn(SemId) -> Option<&'lib Ty<LibRef>>,
+    ) -> Option<Vec<usize>> {
+        let refs = match self {
+            Ty::Struct(fields) => fields.iter().map(|field| &field.ty).collect::<Vec<_>>(),
+            Ty::Tuple(fields) => fields.iter().collect(),
+            _ => return None,
+        };
+        let mut offset = 0usize;
+        let mut offsets = Vec::with_capacity(refs.len());
+        for r in refs {
+            offsets.push(offset);
+            let len = Self::encoded_len_ref(r, &resolver, &mut BTreeSet::new())?;
+            offset = offset.checked_add(len)?;
+        }
+        Some(offsets)
+    }
+
+    fn encoded_len_ref<'lib>(
+        r: &Ref,
+        resolver: &LibResolver<'_, 'lib>,
+        visited: &mut BTreeSet<SemId>,
+    ) -> Option<usize> {
+        match (r.as_inline(), r.referenced_id()) {
+            (Some(ty), _) => ty.encoded_len_inner(resolver, visited),
+            (None, Some(sem_id)) => {
+                // Recursive types always contain a collection or a union breaking the recursion,
+                // so they can't have a fixed length.
+                if !visited.insert(sem_id) {
+                    return None;
+                }
+                let len = resolver(sem_id)?.encoded_len_inner(resolver, visited);
+                visited.remove(&sem_id);
+                len
+            }
+            (None, None) => unreachable!("library type reference is neither inline nor named"),
+        }
+    }
+
+    fn encoded_len_inner<'lib>(
+        &self,
+        resolver: &LibResolver<'_, 'lib>,
+        visited: &mut BTreeSet<SemId>,
+    ) -> Option<usize> {
+        let collection = |sizing: Sizing, item: Option<usize>| {
+            (sizing.min == sizing.max)
+                .then(|| {
+                    item?.checked_mul(sizing.max as usize)?.checked_add(len_prefix(sizing) as usize)
+                })
+                .flatten()
+        };
+        match self {
+            Ty::Primitive(prim) => Some(prim.byte_size() as usize),
+            Ty::UnicodeChar => None,
+            Ty::Enum(_) => Some(1),
+            Ty::Union(variants) => {
+                let mut len = None;
+                for ty in variants.values() {
+                    let variant_len = Self::encoded_len_ref(ty, resolver, visited)?;
+                    if *len.get_or_insert(variant_len) != variant_len {
+                        return None;
+                    }
+                }
+                len.map(|len| len + 1)
+            }
+            Ty::Tuple(fields) => fields.iter().try_fold(0usize, |sum, ty| {
+                sum.checked_add(Self::encoded_len_ref(ty, resolver, visited)?)
+            }),
+            Ty::Struct(fields) => fields.iter().try_fold(0usize, |sum, field| {
+                sum.checked_add(Self::encoded_len_ref(&field.ty, resolver, visited)?)
+            }),
+            Ty::Array(ty, len) => {
+                Self::encoded_len_ref(ty, resolver, visited)?.checked_mul(*len as usize)
+            }
+            Ty::List(ty, sizing) | Ty::Set(ty, sizing) => {
+                collection(*sizing, Self::encoded_len_ref(ty, resolver, visited))
+            }
+            Ty::Map(key, ty, sizing) => {
+                let item = Self::encoded_len_ref(key, resolver, visited).and_then(|key_len| {
+                    key_len.checked_add(Self::encoded_len_ref(ty, resolver, visited)?)
+                });
+                collection(*sizing, item)
+            }
+        }
+    }
+
+    /// Detects whether the type contains floating-point numbers anywhere in its definition.
+    ///
+    /// Named and external type references are resolved with the provided
+    /// `resolver`; types which can't be resolved are considered to contain no
+    /// floats.
+    pub fn contains_float<'lib>(
+        &self,
+        resolver: impl Fn(SemId) -> Option<&'lib Ty<LibRef>>,
+    ) -> bool {
+        self.float_path(resolver).is_some()
+    }
+
+    /// Returns path to the first floating-point number found inside the type,
+    /// or `None` if the type doesn't contain floats. Path items are field and
+    /// variant names, or positions of the unnamed fields.
+    pub fn float_path<'lib>(
+        &self,
+        resolver: impl Fn(SemId) -> Option<&'lib Ty<LibRef>>,
+    ) -> Option<Vec<String>> {
+        let mut path = vec![];
+        self.float_path_inner(&resolver, &mut BTreeSet::new(), &mut path).then_some(path)
+    }
+
+    fn float_path_inner<'lib>(
+        &self,
+        resolver: &LibResolver<'_, 'lib>,
+        visited: &mut BTreeSet<SemId>,
+        path: &mut Vec<String>,
+    ) -> bool {
+        if let Ty::Primitive(prim) = self {
+            return prim.info().ty == NumCls::Float;
+        }
+        for (pos, (r, case)) in self.type_refs().enumerate() {
+            path.push(case.map(|case| case.to_string()).unwrap_or_else(|| pos.to_string()));
+            let found = match (r.as_inline(), r.referenced_id()) {
+                (Some(ty), _) => ty.float_path_inner(resolver, visited, path),
+                (None, Some(sem_id)) => {
+                    visited.insert(sem_id)
+                        && resolver(sem_id)
+                            .map(|ty| ty.float_path_inner(resolver, visited, path))
+                            .unwrap_or_default()
+                }
+                (None, None) => unreachable!("library type reference is neither inline nor named"),
+            };
+            if found {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+}
 
 #[derive(Clone, Eq, PartialEq, Debug, Display)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -58,14 +451,100 @@ impl ExternRef {
     pub fn with(lib_id: TypeLibId, sem_id: SemId) -> ExternRef { ExternRef { lib_id, sem_id } }
 }
 
+/// Deserialization of the library type references, accepting both the current adjacently tagged
+/// representation and the externally tagged one produced by the earlier versions of the crate.
+#[cfg(feature = "serde")]
+mod serde_compat {
+    use std::fmt;
+
+    use serde::de::{self, EnumAccess, MapAccess, VariantAccess, Visitor};
+    use serde::{Deserialize, Deserializer};
+
+    use super::*;
+
+    macro_rules! deserialize_ref {
+        ($ty:ident { $($tag:literal => $variant:ident($inner:ty)),+ $(,)? }) => {
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    const VARIANTS: &[&str] = &[$($tag),+];
+
+                    struct RefVisitor;
+
+                    impl<'de> Visitor<'de> for RefVisitor {
+                        type Value = $ty;
+
+                        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                            f.write_str("library type reference")
+                        }
+
+                        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<$ty, A::Error> {
+                            let Some(key) = map.next_key::<String>()? else {
+                                return Err(de::Error::missing_field("type"));
+                            };
+                            let tag = if key == "type" {
+                                let tag = map.next_value::<String>()?;
+                                if map.next_key::<String>()?.as_deref() != Some("value") {
+                                    return Err(de::Error::missing_field("value"));
+                                }
+                                tag
+                            } else {
+                                key
+                            };
+                            let r = match tag.as_str() {
+                                $($tag => $ty::$variant(map.next_value::<$inner>()?),)+
+                                other => return Err(de::Error::unknown_variant(other, VARIANTS)),
+                            };
+                            if map.next_key::<de::IgnoredAny>()?.is_some() {
+                                return Err(de::Error::custom("unexpected data in type reference"));
+                            }
+                            Ok(r)
+                        }
+
+                        fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<$ty, A::Error> {
+                            let (tag, variant) = data.variant::<String>()?;
+                            Ok(match tag.as_str() {
+                                $($tag => $ty::$variant(variant.newtype_variant::<$inner>()?),)+
+                                other => return Err(de::Error::unknown_variant(other, VARIANTS)),
+                            })
+                        }
+                    }
+
+                    deserializer.deserialize_any(RefVisitor)
+                }
+            }
+        };
+    }
+
+    deserialize_ref!(InlineRef {
+        "inline" => Inline(Ty<InlineRef1>),
+        "named" => Named(SemId),
+        "extern" => Extern(ExternRef),
+    });
+    deserialize_ref!(InlineRef1 {
+        "inline" => Inline(Ty<InlineRef2>),
+        "named" => Named(SemId),
+        "extern" => Extern(ExternRef),
+    });
+    deserialize_ref!(InlineRef2 {
+        "named" => Named(SemId),
+        "extern" => Extern(ExternRef),
+    });
+    deserialize_ref!(LibRef {
+        "inline" => Inline(Ty<InlineRef>),
+        "named" => Named(SemId),
+        "extern" => Extern(ExternRef),
+    });
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, From)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = order, dumb = { InlineRef::Inline(Ty::strict_dumb()) })]
 #[cfg_attr(
     feature = "serde",
-    derive(Serialize, Deserialize),
-    serde(crate = "serde_crate", rename_all = "camelCase")
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase", tag = "type", content = "value")
 )]
+#[allow(clippy::large_enum_variant)]
 pub enum InlineRef {
     #[from]
     Inline(Ty<InlineRef1>),
@@ -86,6 +565,12 @@ impl TypeRef for InlineRef {
             _ => false,
         }
     }
+    fn is_unit(&self) -> bool {
+        match self {
+            InlineRef::Inline(ty) => *ty == Ty::UNIT,
+            _ => false,
+        }
+    }
     fn is_unicode_char(&self) -> bool {
         match self {
             InlineRef::Inline(ty) => ty.is_unicode_char(),
@@ -109,8 +594,8 @@ impl Display for InlineRef {
 #[strict_type(lib = STRICT_TYPES_LIB, tags = order, dumb = { InlineRef1::Inline(Ty::strict_dumb()) })]
 #[cfg_attr(
     feature = "serde",
-    derive(Serialize, Deserialize),
-    serde(crate = "serde_crate", rename_all = "camelCase")
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase", tag = "type", content = "value")
 )]
 pub enum InlineRef1 {
     #[from]
@@ -132,6 +617,12 @@ impl TypeRef for InlineRef1 {
             _ => false,
         }
     }
+    fn is_unit(&self) -> bool {
+        match self {
+            InlineRef1::Inline(ty) => *ty == Ty::UNIT,
+            _ => false,
+        }
+    }
     fn is_unicode_char(&self) -> bool {
         match self {
             InlineRef1::Inline(ty) => ty.is_unicode_char(),
@@ -155,8 +646,8 @@ impl Display for InlineRef1 {
 #[strict_type(lib = STRICT_TYPES_LIB, tags = order, dumb = { InlineRef2::Named(SemId::strict_dumb()) })]
 #[cfg_attr(
     feature = "serde",
-    derive(Serialize, Deserialize),
-    serde(crate = "serde_crate", rename_all = "camelCase")
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase", tag = "type", content = "value")
 )]
 pub enum InlineRef2 {
     Named(SemId),
@@ -167,6 +658,7 @@ impl TypeRef for InlineRef2 {
     fn is_compound(&self) -> bool { false }
     fn is_byte(&self) -> bool { false }
     fn is_unicode_char(&self) -> bool { false }
+    fn is_unit(&self) -> bool { false }
 }
 
 impl Display for InlineRef2 {
@@ -183,9 +675,10 @@ impl Display for InlineRef2 {
 #[strict_type(lib = STRICT_TYPES_LIB, tags = order, dumb = { LibRef::Inline(Ty::strict_dumb()) })]
 #[cfg_attr(
     feature = "serde",
-    derive(Serialize, Deserialize),
-    serde(crate = "serde_crate", rename_all = "camelCase")
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase", tag = "type", content = "value")
 )]
+#[allow(clippy::large_enum_variant)]
 pub enum LibRef {
     #[from]
     Inline(Ty<InlineRef>),
@@ -193,6 +686,16 @@ pub enum LibRef {
     Extern(ExternRef),
 }
 
+impl LibRef {
+    /// Constructs reference to a named type defined in the same library.
+    pub fn named(sem_id: SemId) -> Self { LibRef::Named(sem_id) }
+
+    /// Constructs reference to a type defined in an external library.
+    pub fn external(lib_id: TypeLibId, sem_id: SemId) -> Self {
+        LibRef::Extern(ExternRef::with(lib_id, sem_id))
+    }
+}
+
 impl TypeRef for LibRef {
     fn is_compound(&self) -> bool {
         match self {
@@ -206,6 +709,12 @@ impl TypeRef for LibRef {
             _ => false,
         }
     }
+    fn is_unit(&self) -> bool {
+        match self {
+            LibRef::Inline(ty) => *ty == Ty::UNIT,
+            _ => false,
+        }
+    }
     fn is_unicode_char(&self) -> bool {
         match self {
             LibRef::Inline(ty) => ty.is_unicode_char(),
@@ -264,8 +773,28 @@ impl Display for Dependency {
     }
 }
 
+/// Types defined by a library. A library always defines at least one type, so libraries without
+/// types are unrepresentable by design.
 pub type TypeMap = Confined<BTreeMap<TypeName, Ty<LibRef>>, 1, { u16::MAX as usize }>;
 
+/// Error indicating that a library doesn't define a type with the given name.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("library doesn't define type `{0}`.")]
+pub struct UnknownTypeName(pub TypeName);
+
+/// Errors modifying types of a library with [`TypeLib::insert_type`] and
+/// [`TypeLib::remove_type`].
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum TypeMapError {
+    /// library already contains {count} types, which is the maximum of {limit} types, and can't
+    /// accept more.
+    Full { count: usize, limit: usize },
+
+    /// type `{0}` is the only type of the library and can't be removed.
+    LastType(TypeName),
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(
@@ -285,9 +814,89 @@ pub struct TypeLib {
     pub types: TypeMap,
 }
 
+/// Orders libraries by their names, breaking ties by the library ids. Useful for presenting
+/// library lists to humans in a stable order.
+pub fn cmp_by_name_then_id(a: &TypeLib, b: &TypeLib) -> Ordering {
+    a.name.cmp(&b.name).then_with(|| a.id().cmp(&b.id()))
+}
+
+/// Builds the library twice with `build`, checking that both builds produce the same library
+/// id and byte-identical strict serialization. Returns the library for further checks, like
+/// [`TypeLib::assert_id`] or [`TypeLib::assert_snapshot`].
+///
+/// Intended for tests of the crates defining their own libraries, detecting non-determinism in
+/// the transpilation of their types.
+#[cfg(any(test, feature = "test-helpers"))]
+#[track_caller]
+pub fn assert_stable_transpile(build: impl Fn() -> TypeLib) -> TypeLib {
+    use amplify::confinement::U24 as U24MAX;
+    use strict_encoding::StrictSerialize;
+
+    let serialize = |lib: &TypeLib| {
+        lib.to_strict_serialized::<U24MAX>().expect("library too large to be serialized")
+    };
+    let lib = build();
+    let rebuilt = build();
+    assert_eq!(lib.id(), rebuilt.id(), "library id changes between the builds");
+    assert_eq!(serialize(&lib), serialize(&rebuilt), "library data changes between the builds");
+    lib
+}
+
+/// Reports type names defined by more than one of the given libraries, together with the names
+/// of all libraries defining them. Useful for detecting conflicts which have to be resolved with
+/// aliases before the libraries are linked into a type system.
+pub fn name_collisions(libs: &[TypeLib]) -> BTreeMap<TypeName, Vec<LibName>> {
+    let mut defined = BTreeMap::<TypeName, Vec<LibName>>::new();
+    for lib in libs {
+        for name in lib.types.keys() {
+            defined.entry(name.clone()).or_default().push(lib.name.clone());
+        }
+    }
+    defined.retain(|_, libs| libs.len() > 1);
+    defined
+}
+
 impl TypeLib {
+    /// Constructs library without dependencies, defining a single type.
+    ///
+    /// The type may reference itself by its semantic id; since the library has no other types,
+    /// any other named reference is reported as [`CompileError::DanglingRef`].
+    pub fn single(
+        name: LibName,
+        type_name: TypeName,
+        ty: Ty<LibRef>,
+    ) -> Result<Self, CompileError> {
+        let lib = TypeLib {
+            name,
+            dependencies: default!(),
+            extern_types: default!(),
+            types: Confined::with((type_name, ty)),
+        };
+        if let Err(dangling) = lib.verify_ids() {
+            let (within, unknown) = dangling[0].clone();
+            return Err(CompileError::DanglingRef { within, unknown });
+        }
+        Ok(lib)
+    }
+
+    /// Constructs dependency entry for other libraries using this one.
+    ///
+    /// The library id is computed anew on each call, so the entry always matches the current
+    /// state of the library; call it after all modifications to the library are done.
     pub fn to_dependency(&self) -> Dependency { Dependency::with(self.id(), self.name.clone()) }
 
+    /// Returns dependency imported under the given name.
+    pub fn dependency(&self, name: &LibName) -> Option<&Dependency> {
+        self.dependencies.iter().find(|dep| &dep.name == name)
+    }
+
+    /// Returns name under which a library with the given id is imported as a dependency.
+    ///
+    /// A library can't be imported more than once, so there may be at most one such name.
+    pub fn alias_for(&self, id: TypeLibId) -> Option<&LibName> {
+        self.dependencies.iter().find(|dep| dep.id == id).map(|dep| &dep.name)
+    }
+
     pub fn import(&mut self, dependency: Dependency) -> Result<(), CompileError> {
         if self.dependencies.contains(&dependency) {
             return Err(CompileError::DuplicatedDependency(dependency));
@@ -304,5 +913,800 @@ impl TypeLib {
         Ok(())
     }
 
+    /// Adds a type to the library or replaces the existing type with the same name, returning
+    /// the replaced type.
+    pub fn insert_type(
+        &mut self,
+        name: TypeName,
+        ty: Ty<LibRef>,
+    ) -> Result<Option<Ty<LibRef>>, TypeMapError> {
+        if let Some(existing) = self.types.get_mut(&name) {
+            return Ok(Some(std::mem::replace(existing, ty)));
+        }
+        let count = self.types.len();
+        self.types.insert(name, ty).map_err(|_| TypeMapError::Full {
+            count,
+            limit: u16::MAX as usize,
+        })
+    }
+
+    /// Removes a type from the library, returning it, or `None` if there is no such type.
+    /// Libraries always define at least one type, thus the last type can't be removed.
+    pub fn remove_type(&mut self, name: &TypeName) -> Result<Option<Ty<LibRef>>, TypeMapError> {
+        self.types.remove(name).map_err(|_| TypeMapError::LastType(name.clone()))
+    }
+
+    /// Renames the library, returning its new id.
+    ///
+    /// Since the library name is committed to in [`TypeLib::id`], the id changes after the
+    /// renaming. Other libraries depending on this one reference it by the old id and name, so
+    /// their [`Dependency`] records (and, consequently, their own ids) become invalid and must
+    /// be updated by the caller.
+    pub fn rename(&mut self, new_name: impl Into<LibName>) -> TypeLibId {
+        self.name = new_name.into();
+        self.id()
+    }
+
+    /// Checks that the library id matches the `expected` one, panicking otherwise.
+    ///
+    /// Intended for pinning library ids in tests, such that any change to the library types is
+    /// detected. The panic message contains both ids and a hint on how to update them.
+    #[cfg(any(test, feature = "test-helpers"))]
+    #[track_caller]
+    pub fn assert_id(&self, expected: &str) {
+        let id = self.id().to_string();
+        if id != expected {
+            panic!(
+                "the id of the library {} has changed\n  expected: {expected}\n     found: \
+                 {id}\nif the change in the library types is intended, update the pinned id and \
+                 regenerate library files (for the standard libraries run `cargo run --features \
+                 armor --bin <lib>-stl`)",
+                self.name
+            );
+        }
+    }
+
+    /// Verifies that all references to the library types by their semantic ids match the ids
+    /// computed from the type definitions.
+    ///
+    /// Library doesn't store ids of its own types, which are always re-computed from their names
+    /// and definitions; however, the types reference each other by these ids. If a type
+    /// definition was modified (for instance, in a library received from an untrusted source),
+    /// references to it become dangling. The function returns list of all such references as
+    /// pairs of the name of the type containing the reference and the referenced id.
+    ///
+    /// References to the types from other libraries are not verified.
+    pub fn verify_ids(&self) -> Result<(), Vec<(TypeName, SemId)>> {
+        let ids =
+            self.types.iter().map(|(name, ty)| ty.sem_id_named(name)).collect::<BTreeSet<_>>();
+        let mut dangling = vec![];
+        for (name, ty) in &self.types {
+            let mut refs = vec![];
+            collect_named_refs(ty, &mut refs);
+            dangling.extend(
+                refs.into_iter().filter(|id| !ids.contains(id)).map(|id| (name.clone(), id)),
+            );
+        }
+        if dangling.is_empty() {
+            Ok(())
+        } else {
+            Err(dangling)
+        }
+    }
+
+    /// Checks whether every type of this library is present in the `other` library under the
+    /// same name and with the same semantic id, and whether all dependencies of this library
+    /// are also dependencies of the `other` one.
+    ///
+    /// Useful for ensuring that a restricted profile of a library stays consistent with the
+    /// full library.
+    pub fn is_subset_of(&self, other: &TypeLib) -> bool {
+        self.subset_diff(other).is_empty()
+            && self.dependencies.iter().all(|dep| other.dependencies.iter().any(|d| d.id == dep.id))
+    }
+
+    /// Returns names of the library types which are absent from the `other` library or are
+    /// defined there differently.
+    pub fn subset_diff(&self, other: &TypeLib) -> Vec<TypeName> {
+        self.types
+            .iter()
+            .filter(|(name, ty)| {
+                other.types.get(*name).map(|t| t.sem_id_named(name)) != Some(ty.sem_id_named(name))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Returns types which are not reachable from `roots`, i.e. which are neither listed there nor
+    /// transitively referenced by the types listed there. Names in `roots` which are not defined
+    /// by the library are ignored.
+    pub fn unused_types<'a>(&'a self, roots: &BTreeSet<TypeName>) -> BTreeSet<&'a TypeName> {
+        let used = self.reachable(roots.iter().filter(|root| self.types.contains_key(*root)));
+        self.types.keys().filter(|name| !used.contains(*name)).collect()
+    }
+
+    /// Returns `root` and all library types transitively referenced by it, i.e. the types which
+    /// must be known to use the `root` type. Types from the dependencies are not included.
+    pub fn reachable_from(&self, root: &TypeName) -> Result<BTreeSet<TypeName>, UnknownTypeName> {
+        if !self.types.contains_key(root) {
+            return Err(UnknownTypeName(root.clone()));
+        }
+        Ok(self.reachable([root]))
+    }
+
+    fn reachable<'a>(&self, roots: impl IntoIterator<Item = &'a TypeName>) -> BTreeSet<TypeName> {
+        let index = self
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), name))
+            .collect::<BTreeMap<_, _>>();
+        let mut reached = BTreeSet::new();
+        let mut queue = vec![];
+        for root in roots {
+            if reached.insert(root.clone()) {
+                queue.push(self.types.get(root).expect("root is present in the library"));
+            }
+        }
+        while let Some(ty) = queue.pop() {
+            let mut refs = vec![];
+            collect_named_refs(ty, &mut refs);
+            for name in refs.iter().filter_map(|id| index.get(id)) {
+                if reached.insert((*name).clone()) {
+                    queue.push(self.types.get(*name).expect("indexed type"));
+                }
+            }
+        }
+        reached
+    }
+
+    /// Returns fully qualified name of the type defined in this library.
+    ///
+    /// The function doesn't check whether the type is actually present in the library.
+    pub fn fqn(&self, ty: &TypeName) -> TypeFqn { TypeFqn::with(self.name.clone(), ty.clone()) }
+
+    /// Returns path (starting with the type name) to the first floating-point
+    /// number found in the library types, or `None` if the library doesn't use
+    /// floats. Types from the library dependencies are not inspected.
+    pub fn float_path(&self) -> Option<String> {
+        let index = self
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), ty))
+            .collect::<BTreeMap<_, _>>();
+        self.types.iter().find_map(|(name, ty)| {
+            let path = ty.float_path(|id| index.get(&id).copied())?;
+            Some(format!("{name}.{}", path.join(".")))
+        })
+    }
+
+    /// Iterates over every node of every library type, returning the name of the root type, the
+    /// path from the root to the node and the semantic id of the node type.
+    ///
+    /// Types referenced by name are descended into unless they already appear on the path from
+    /// the root, which terminates recursive types; types from other libraries are reported but
+    /// not descended into.
+    pub fn flatten_nodes(&self) -> impl Iterator<Item = (TypeName, Path, SemId)> + '_ {
+        let index = self
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), ty))
+            .collect::<BTreeMap<_, _>>();
+        let mut nodes = vec![];
+        for (name, ty) in &self.types {
+            let sem_id = ty.sem_id_named(name);
+            let mut flattener = Flattener {
+                root: name,
+                index: &index,
+                stack: bset![sem_id],
+                nodes: &mut nodes,
+            };
+            flattener.nodes.push((name.clone(), Path::new(), sem_id));
+            flattener.walk(ty, &mut Path::new());
+        }
+        nodes.into_iter()
+    }
+
     // TODO: Check that all dependencies are used
 }
+
+fn collect_named_refs<Ref: LibSubref>(ty: &Ty<Ref>, refs: &mut Vec<SemId>) {
+    for (r, _) in ty.type_refs() {
+        if let Some(inline) = r.as_inline() {
+            collect_named_refs(inline, refs);
+        }
+        refs.extend(r.as_sem_id());
+    }
+}
+
+struct Flattener<'lib, 'a> {
+    root: &'lib TypeName,
+    index: &'a BTreeMap<SemId, &'lib Ty<LibRef>>,
+    stack: BTreeSet<SemId>,
+    nodes: &'a mut Vec<(TypeName, Path, SemId)>,
+}
+
+impl Flattener<'_, '_> {
+    fn walk<Ref: LibSubref>(&mut self, ty: &Ty<Ref>, path: &mut Path) {
+        for (pos, (r, _)) in ty.type_refs().enumerate() {
+            let step = ty.step_at(pos as u8).expect("type reference without path step");
+            path.push(step).expect("type nesting exceeds path length limit");
+            match (r.as_inline(), r.referenced_id()) {
+                (Some(inline), _) => {
+                    self.nodes.push((self.root.clone(), path.clone(), inline.sem_id_unnamed()));
+                    self.walk(inline, path);
+                }
+                (None, Some(sem_id)) => {
+                    self.nodes.push((self.root.clone(), path.clone(), sem_id));
+                    if let Some(named) = self.index.get(&sem_id).copied() {
+                        if self.stack.insert(sem_id) {
+                            self.walk(named, path);
+                            self.stack.remove(&sem_id);
+                        }
+                    }
+                }
+                (None, None) => unreachable!("library type reference is neither inline nor named"),
+            }
+            path.pop();
+        }
+    }
+}
+
+/// Leaf of a type reported by [`Ty::for_each_leaf`].
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+pub enum Leaf {
+    /// Type which is not descended into, with its references replaced by semantic ids.
+    #[display(inner)]
+    Type(Ty<SemId>),
+
+    /// Reference to a type from other library, which is not followed.
+    #[display(inner)]
+    Extern(ExternRef),
+
+    /// Reference to a library type which can't be followed, since it is either absent from the
+    /// library or already present on the path from the root. This happens only with libraries
+    /// whose type definitions don't match the semantic ids they are referenced by.
+    #[display("{0}!")]
+    Unresolved(SemId),
+}
+
+impl<Ref: LibSubref> Ty<Ref> {
+    /// Calls `f` for each leaf of the type with the path from the type root to the leaf.
+    ///
+    /// Leaves are primitives, unicode characters, enums, byte arrays, and byte and unicode
+    /// strings, reported as [`Leaf::Type`]. For other collections the item type is descended
+    /// into, with the collection step appended to the path.
+    ///
+    /// Types referenced by name are resolved with `lib` and descended into. References to the
+    /// types from other libraries are reported as [`Leaf::Extern`] and not followed; named
+    /// references which can't be followed are reported as [`Leaf::Unresolved`].
+    pub fn for_each_leaf(&self, lib: &TypeLib, mut f: impl FnMut(&Path, &Leaf)) {
+        let index = lib
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), ty))
+            .collect::<BTreeMap<_, _>>();
+        let mut walker = LeafWalker {
+            index: &index,
+            stack: empty!(),
+            f: &mut f,
+        };
+        walker.walk(self, &mut Path::new());
+    }
+
+    fn as_leaf(&self) -> Option<Ty<SemId>> {
+        Some(match self {
+            Ty::Primitive(prim) => Ty::Primitive(*prim),
+            Ty::UnicodeChar => Ty::UnicodeChar,
+            Ty::Enum(variants) => Ty::Enum(variants.clone()),
+            Ty::Array(r, len) if r.is_byte() => Ty::Array(r.id(), *len),
+            Ty::List(r, sizing) if r.is_byte() || r.is_unicode_char() => Ty::List(r.id(), *sizing),
+            _ => return None,
+        })
+    }
+}
+
+struct LeafWalker<'lib, 'a, F: FnMut(&Path, &Leaf)> {
+    index: &'a BTreeMap<SemId, &'lib Ty<LibRef>>,
+    stack: BTreeSet<SemId>,
+    f: &'a mut F,
+}
+
+impl<F: FnMut(&Path, &Leaf)> LeafWalker<'_, '_, F> {
+    fn walk<Ref: LibSubref>(&mut self, ty: &Ty<Ref>, path: &mut Path) {
+        if let Some(leaf) = ty.as_leaf() {
+            (self.f)(path, &Leaf::Type(leaf));
+            return;
+        }
+        for (pos, (r, _)) in ty.type_refs().enumerate() {
+            let step = ty.step_at(pos as u8).expect("type reference without path step");
+            path.push(step).expect("type nesting exceeds path length limit");
+            if let Some(inline) = r.as_inline() {
+                self.walk(inline, path);
+            } else if let Some(ext) = r.as_extern() {
+                (self.f)(path, &Leaf::Extern(ext.clone()));
+            } else if let Some(sem_id) = r.as_sem_id() {
+                match self.index.get(&sem_id).copied() {
+                    Some(named) if self.stack.insert(sem_id) => {
+                        self.walk(named, path);
+                        self.stack.remove(&sem_id);
+                    }
+                    _ => (self.f)(path, &Leaf::Unresolved(sem_id)),
+                }
+            }
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::TinyVec;
+    use amplify::num::apfloat::ieee;
+    use strict_encoding::{Variant, LIB_NAME_STD};
+
+    use super::*;
+    use crate::ast::{field, NamedFields, UnionVariants, UnnamedFields};
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::LibBuilder;
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Price {
+        lot: u16,
+        amount: ieee::Double,
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Order {
+        id: u64,
+        price: Option<Price>,
+    }
+
+    #[test]
+    fn cmp_name_id() {
+        let std = std_stl();
+        let mut forked = std.clone();
+        forked.types.remove(&tn!("Bool")).unwrap();
+        let mut other = std.clone();
+        other.rename(libname!("Another"));
+
+        let mut libs = [std.clone(), forked.clone(), other.clone()];
+        libs.sort_by(cmp_by_name_then_id);
+        assert_eq!(libs[0], other);
+        let (first, second) =
+            if std.id() < forked.id() { (&std, &forked) } else { (&forked, &std) };
+        assert_eq!(&libs[1], first);
+        assert_eq!(&libs[2], second);
+    }
+
+    #[test]
+    fn lib_ref_kinds() {
+        let lib_id = std_stl().id();
+        let sem_id = SemId::default();
+
+        let named = LibRef::named(sem_id);
+        assert_eq!(named, LibRef::Named(sem_id));
+        assert!(named.is_named() && !named.is_inline() && !named.is_extern());
+
+        let ext = LibRef::external(lib_id, sem_id);
+        assert_eq!(ext, LibRef::Extern(ExternRef::with(lib_id, sem_id)));
+        assert!(ext.is_extern() && !ext.is_named() && !ext.is_inline());
+        assert_eq!(ext.referenced_id(), Some(sem_id));
+
+        let inline = LibRef::from(Ty::<InlineRef>::UNIT);
+        assert!(inline.is_inline() && !inline.is_named() && !inline.is_extern());
+
+        assert_eq!(named.id(), sem_id);
+        assert_eq!(ext.id(), sem_id);
+        assert_eq!(inline.id(), Ty::<SemId>::UNIT.sem_id_unnamed());
+    }
+
+    #[test]
+    fn single() {
+        let std = std_stl();
+        let bool = std.types[&tn!("Bool")].clone();
+        let lib = TypeLib::single(libname!("Single"), tn!("Bool"), bool.clone()).unwrap();
+        assert_eq!(lib.types.len(), 1);
+        assert_eq!(lib.types[&tn!("Bool")], bool);
+        assert!(lib.dependencies.is_empty());
+
+        let unknown = std.types[&tn!("U4")].sem_id_named(&tn!("U4"));
+        let list = Ty::List(LibRef::Named(unknown), Sizing::U8);
+        assert_eq!(
+            TypeLib::single(libname!("Single"), tn!("List"), list).unwrap_err(),
+            CompileError::DanglingRef {
+                within: tn!("List"),
+                unknown
+            }
+        );
+    }
+
+    #[test]
+    fn dependency_lookup() {
+        let std = std_stl();
+        let lib = strict_types_stl();
+        let std_name = libname!(LIB_NAME_STD);
+        assert_eq!(lib.dependency(&std_name), Some(&std.to_dependency()));
+        assert_eq!(lib.dependency(&libname!("Unknown")), None);
+        assert_eq!(lib.alias_for(std.id()), Some(&std_name));
+        assert_eq!(lib.alias_for(lib.id()), None);
+    }
+
+    #[test]
+    fn for_each_leaf() {
+        let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+            .transpile::<Order>()
+            .compile()
+            .unwrap();
+        let mut leaves = vec![];
+        lib.types[&tn!("Order")]
+            .for_each_leaf(&lib, |path, ty| leaves.push(format!("{path} {ty}")));
+        assert_eq!(leaves, [
+            ".id U64",
+            ".price.none ()",
+            ".price.some.0.lot U16",
+            ".price.some.0.amount F64"
+        ]);
+
+        let mut tampered = lib.clone();
+        let price = tampered.types.remove(&tn!("Price")).unwrap().unwrap();
+        let price_id = price.sem_id_named(&tn!("Price"));
+        let mut leaves = vec![];
+        tampered.types[&tn!("Order")]
+            .for_each_leaf(&tampered, |path, leaf| leaves.push((path.to_string(), leaf.clone())));
+        assert_eq!(leaves.last(), Some(&(s!(".price.some.0"), Leaf::Unresolved(price_id))));
+
+        // Walks all StrictTypes library types, including the ones nesting inline types
+        let lib = strict_types_stl();
+        let std_id = std_stl().id();
+        let mut count = 0;
+        let mut externs = 0;
+        for ty in lib.types.values() {
+            ty.for_each_leaf(&lib, |_, leaf| match leaf {
+                Leaf::Type(_) => count += 1,
+                Leaf::Extern(ext) => {
+                    assert_eq!(ext.lib_id, std_id);
+                    externs += 1;
+                }
+                Leaf::Unresolved(_) => panic!("unresolved reference in a valid library"),
+            });
+        }
+        assert!(count > 0);
+        assert!(externs > 0);
+
+        let mut leaves = vec![];
+        lib.types[&tn!("FieldName")]
+            .for_each_leaf(&lib, |path, leaf| leaves.push((path.to_string(), leaf.clone())));
+        assert_eq!(leaves.len(), 2);
+        assert!(leaves.iter().all(|(_, leaf)| matches!(leaf, Leaf::Extern(_))));
+    }
+
+    #[test]
+    fn subset() {
+        let std = std_stl();
+        let mut profile = std.clone();
+        profile.types.remove(&tn!("Bool")).unwrap();
+        assert!(std.is_subset_of(&std));
+        assert!(profile.is_subset_of(&std));
+        assert!(!std.is_subset_of(&profile));
+        assert_eq!(std.subset_diff(&profile), vec![tn!("Bool")]);
+
+        let bool = profile.types.get(&tn!("U1")).unwrap().clone();
+        profile.types.insert(tn!("Bool"), bool).unwrap();
+        assert_eq!(profile.subset_diff(&std), vec![tn!("Bool")]);
+
+        let lib = strict_types_stl();
+        let mut lib_profile = lib.clone();
+        lib_profile.dependencies = none!();
+        assert!(lib_profile.is_subset_of(&lib));
+        assert!(!lib.is_subset_of(&lib_profile));
+        assert!(lib.subset_diff(&lib_profile).is_empty());
+    }
+
+    #[test]
+    fn collisions() {
+        let std = std_stl();
+        let mut other = std.clone();
+        other.rename(libname!("Another"));
+        other.types.remove(&tn!("Bool")).unwrap();
+
+        let collisions = name_collisions(&[std.clone(), strict_types_stl()]);
+        assert!(collisions.is_empty());
+
+        let collisions = name_collisions(&[std.clone(), strict_types_stl(), other]);
+        assert_eq!(collisions.len(), std.types.len() - 1);
+        assert!(!collisions.contains_key(&tn!("Bool")));
+        assert_eq!(collisions[&tn!("U4")], vec![libname!(LIB_NAME_STD), libname!("Another")]);
+    }
+
+    #[test]
+    fn rename() {
+        let mut lib = std_stl();
+        let id = lib.id();
+        let new_id = lib.rename(libname!("Forked"));
+        assert_ne!(new_id, id);
+        assert_eq!(new_id, lib.id());
+        assert_eq!(lib.rename(libname!(LIB_NAME_STD)), id);
+    }
+
+    #[test]
+    fn forbid_floats() {
+        let builder = || {
+            LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()]).transpile::<Order>()
+        };
+        let lib = builder().compile().unwrap();
+        let order = lib.types.get(&tn!("Order")).unwrap();
+        let resolver =
+            |id| lib.types.iter().find(|(name, ty)| ty.sem_id_named(name) == id).map(|(_, ty)| ty);
+        assert!(order.contains_float(resolver));
+        assert!(!Ty::<LibRef>::U64.contains_float(resolver));
+        assert_eq!(
+            builder().forbid_floats().compile().unwrap_err(),
+            CompileError::FloatForbidden {
+                path: s!("Order.price.some.0.amount")
+            }
+        );
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Header {
+        version: u16,
+        id: [u8; 4],
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Packet {
+        header: Header,
+        data: TinyVec<u8>,
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Void {}
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Marker {
+        void: Void,
+        unit: (),
+    }
+
+    #[derive(Clone, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Tagged {
+        marker: Marker,
+        tag: u8,
+    }
+
+    #[test]
+    fn is_fixed_size() {
+        let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+            .transpile::<Packet>()
+            .compile()
+            .unwrap();
+        let resolver =
+            |id| lib.types.iter().find(|(name, ty)| ty.sem_id_named(name) == id).map(|(_, ty)| ty);
+        assert!(lib.types.get(&tn!("Header")).unwrap().is_fixed_size(resolver));
+        assert!(!lib.types.get(&tn!("Packet")).unwrap().is_fixed_size(resolver));
+    }
+
+    #[test]
+    fn is_zero_sized() {
+        let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+            .transpile::<Tagged>()
+            .compile()
+            .unwrap();
+        let resolver =
+            |id| lib.types.iter().find(|(name, ty)| ty.sem_id_named(name) == id).map(|(_, ty)| ty);
+        assert!(lib.types.get(&tn!("Void")).unwrap().is_zero_sized(resolver));
+        assert!(lib.types.get(&tn!("Marker")).unwrap().is_zero_sized(resolver));
+        assert!(!lib.types.get(&tn!("Tagged")).unwrap().is_zero_sized(resolver));
+
+        let unit = LibRef::Inline(Ty::UNIT);
+        let byte = LibRef::Inline(Ty::U8);
+        assert!(Ty::<LibRef>::UNIT.is_zero_sized(resolver));
+        assert!(Ty::<LibRef>::Array(unit.clone(), 16).is_zero_sized(resolver));
+        assert!(Ty::<LibRef>::Array(byte.clone(), 0).is_zero_sized(resolver));
+        assert!(!Ty::<LibRef>::Array(byte.clone(), 1).is_zero_sized(resolver));
+        // Collections always encode their length, even when empty or holding units
+        assert!(!Ty::<LibRef>::list(byte, Sizing::fixed(0)).is_zero_sized(resolver));
+        assert!(!Ty::<LibRef>::list(unit, Sizing::U8).is_zero_sized(resolver));
+    }
+
+    #[test]
+    fn encoded_len_fixed() {
+        let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+            .transpile::<Order>()
+            .compile()
+            .unwrap();
+        let resolver =
+            |id| lib.types.iter().find(|(name, ty)| ty.sem_id_named(name) == id).map(|(_, ty)| ty);
+        let price = lib.types.get(&tn!("Price")).unwrap();
+        assert_eq!(price.encoded_len_fixed(resolver), Some(2 + 8));
+        assert_eq!(price.field_offsets(resolver), Some(vec![0, 2]));
+        // `Option<Price>` has variants of a different length
+        let order = lib.types.get(&tn!("Order")).unwrap();
+        assert_eq!(order.encoded_len_fixed(resolver), None);
+        assert_eq!(order.field_offsets(resolver), None);
+
+        let byte = LibRef::Inline(Ty::U8);
+        assert_eq!(Ty::<LibRef>::Array(byte.clone(), 32).encoded_len_fixed(resolver), Some(32));
+        assert_eq!(
+            Ty::<LibRef>::list(byte.clone(), Sizing::fixed(4)).encoded_len_fixed(resolver),
+            Some(5)
+        );
+        assert_eq!(Ty::<LibRef>::list(byte, Sizing::U8).encoded_len_fixed(resolver), None);
+        assert_eq!(Ty::<LibRef>::UNICODE.encoded_len_fixed(resolver), None);
+    }
+
+    #[test]
+    fn flatten_nodes() {
+        let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+            .transpile::<Order>()
+            .compile()
+            .unwrap();
+        let nodes = lib
+            .flatten_nodes()
+            .filter(|(root, ..)| root == &tn!("Order"))
+            .map(|(_, path, _)| path.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [
+            "",
+            ".id",
+            ".price",
+            ".price.none",
+            ".price.some",
+            ".price.some.0",
+            ".price.some.0.lot",
+            ".price.some.0.amount"
+        ]);
+        let (_, _, id) = lib.flatten_nodes().find(|(_, path, _)| path.is_empty()).unwrap();
+        assert!(lib.types.iter().any(|(name, ty)| ty.sem_id_named(name) == id));
+    }
+
+    #[test]
+    #[should_panic(expected = "the id of the library Std has changed")]
+    fn assert_id() { std_stl().assert_id(crate::stl::LIB_ID_STRICT_TYPES) }
+
+    #[test]
+    fn about() {
+        let std = std_stl();
+        let (name, ty) = std.types.first_key_value().unwrap();
+        let sem_id = ty.sem_id_named(name);
+        let short = sem_id.to_short_string();
+
+        assert_eq!(LibRef::Named(sem_id).about(), format!("named type {short}"));
+        assert_eq!(InlineRef2::Named(sem_id).about(), format!("named type {short}"));
+        assert_eq!(
+            LibRef::Extern(ExternRef::with(std.id(), sem_id)).about(),
+            format!("extern type {short} from library {}", std.id().to_baid64_mnemonic())
+        );
+        assert_eq!(LibRef::Inline(Ty::U8).about(), "inline primitive U8");
+        assert_eq!(LibRef::Inline(Ty::Array(InlineRef::Named(sem_id), 4)).about(), "inline array");
+        assert_eq!(InlineRef::Inline(Ty::UNICODE).about(), "inline unicode");
+    }
+
+    #[test]
+    fn verify_ids() {
+        let lib = strict_types_stl();
+        assert_eq!(lib.verify_ids(), Ok(()));
+
+        let mut tampered = lib.clone();
+        let name = tn!("TypeName");
+        let id = lib.types.get(&name).unwrap().sem_id_named(&name);
+        *tampered.types.get_mut(&name).unwrap() =
+            Ty::Tuple(UnnamedFields::try_from(vec![LibRef::Inline(Ty::U8)]).unwrap());
+        let errs = tampered.verify_ids().unwrap_err();
+        assert!(errs.iter().all(|(_, dangling)| *dangling == id));
+        assert!(errs.contains(&(tn!("SymbolRef"), id)));
+    }
+
+    #[test]
+    fn reachable_from() {
+        let lib = strict_types_stl();
+        assert_eq!(lib.reachable_from(&tn!("Sizing")), Ok(bset![tn!("Sizing")]));
+        let reached = lib.reachable_from(&tn!("TypeLib")).unwrap();
+        assert!(reached.contains(&tn!("TypeLib")));
+        assert!(reached.contains(&tn!("Dependency")));
+        assert!(reached.contains(&tn!("Sizing")));
+        assert!(!reached.contains(&tn!("TypeSystem")));
+        for name in &reached {
+            assert!(lib.reachable_from(name).unwrap().is_subset(&reached));
+        }
+        assert_eq!(lib.reachable_from(&tn!("Absent")), Err(UnknownTypeName(tn!("Absent"))));
+    }
+
+    #[test]
+    fn unused_types() {
+        let mut lib = TypeLib::single(libname!("Test"), tn!("Root"), Ty::U8).unwrap();
+        let leaf_id = Ty::<LibRef>::U16.sem_id_named(&tn!("Leaf"));
+        let middle = Ty::list(LibRef::named(leaf_id), Sizing::U8);
+        let middle_id = middle.sem_id_named(&tn!("Middle"));
+        lib.insert_type(tn!("Leaf"), Ty::U16).unwrap();
+        lib.insert_type(tn!("Middle"), middle).unwrap();
+        lib.insert_type(tn!("Top"), Ty::list(LibRef::named(middle_id), Sizing::U8)).unwrap();
+
+        // Types referenced only from other unused types are unused as well
+        let unused = |roots| lib.unused_types(&roots).into_iter().cloned().collect::<BTreeSet<_>>();
+        assert_eq!(unused(bset![tn!("Root")]), bset![tn!("Leaf"), tn!("Middle"), tn!("Top")]);
+        assert_eq!(unused(bset![tn!("Root"), tn!("Middle")]), bset![tn!("Top")]);
+        assert!(unused(bset![tn!("Root"), tn!("Top"), tn!("Absent")]).is_empty());
+
+        let lib = strict_types_stl();
+        let unused = lib.unused_types(&bset![tn!("TypeLib")]);
+        assert!(unused.contains(&tn!("TypeSystem")));
+        assert!(!unused.contains(&tn!("Sizing")));
+    }
+
+    #[test]
+    fn display_alignment() {
+        let mut lib = TypeLib::single(libname!("Test"), tn!("Short"), Ty::U8).unwrap();
+        lib.insert_type(tn!("TransactionOutputWitness"), Ty::U16).unwrap();
+        let s = lib.to_string();
+        let columns = s
+            .lines()
+            .filter(|line| line.starts_with("data "))
+            .map(|line| line.find(" : ").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, [29, 29]);
+        assert!(format!("{lib:20}").contains("data Short                : U8"));
+
+        let fields = ["a", "b", "c"].map(|name| field(name, LibRef::Inline(Ty::U8)));
+        let struc = Ty::Struct(NamedFields::try_from(fields.to_vec()).unwrap());
+        lib.insert_type(tn!("Struct"), struc).unwrap();
+        let union = UnionVariants::try_from(bmap! {
+            Variant::named(0, vname!("a")) => LibRef::Inline(Ty::U8),
+            Variant::named(1, vname!("b")) => LibRef::Inline(Ty::U16),
+        })
+        .unwrap();
+        lib.insert_type(tn!("Union"), Ty::Union(union)).unwrap();
+        for (s, column) in [(lib.to_string(), 32), (format!("{lib:40}"), 48)] {
+            let mut definitions = 0;
+            let mut continuations = 0;
+            for line in s.lines() {
+                if line.starts_with("data ") {
+                    assert_eq!(line.find(" : ").unwrap() + 3, column);
+                    definitions += 1;
+                } else if line.trim_start().starts_with([',', '|']) {
+                    assert_eq!(line.len() - line.trim_start().len() + 2, column, "{line}");
+                    continuations += 1;
+                }
+            }
+            assert_eq!((definitions, continuations), (4, 3));
+        }
+    }
+
+    #[test]
+    fn type_map_limits() {
+        let mut lib = TypeLib::single(libname!("Test"), tn!("Ty0"), Ty::U8).unwrap();
+        assert_eq!(lib.remove_type(&tn!("Ty0")), Err(TypeMapError::LastType(tn!("Ty0"))));
+        assert_eq!(lib.insert_type(tn!("Ty0"), Ty::U16), Ok(Some(Ty::U8)));
+        for no in 1..u16::MAX {
+            assert_eq!(lib.insert_type(tn!(format!("Ty{no}")), Ty::U8), Ok(None));
+        }
+        let err = lib.insert_type(tn!("Extra"), Ty::U8).unwrap_err();
+        assert_eq!(err, TypeMapError::Full {
+            count: 65535,
+            limit: 65535
+        });
+        assert_eq!(lib.insert_type(tn!("Ty1"), Ty::U16), Ok(Some(Ty::U8)));
+        assert_eq!(lib.types.get(&tn!("Ty1")), Some(&Ty::U16));
+        assert_eq!(
+            err.to_string(),
+            "library already contains 65535 types, which is the maximum of 65535 types, and can't \
+             accept more."
+        );
+        assert_eq!(lib.remove_type(&tn!("Ty0")), Ok(Some(Ty::U16)));
+        assert_eq!(lib.remove_type(&tn!("Ty0")), Ok(None));
+    }
+}
@@ -23,14 +23,16 @@
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
+use amplify::confinement::{self, MediumOrdSet};
+use amplify::num::u24;
 use amplify::{ByteArray, Bytes32};
 use baid64::{Baid64ParseError, DisplayBaid64, FromBaid64Str};
 use encoding::StrictEncode;
-use sha2::{Digest, Sha256};
+use sha2::Digest;
 use strict_encoding::STRICT_TYPES_LIB;
 
 use crate::ast::SemCommit;
-use crate::{CommitConsume, TypeSystem};
+use crate::{tagged_hasher, CommitConsume, SemId, TypeSystem};
 
 pub const TYPESYS_ID_TAG: [u8; 32] = *b"urn:ubideco:strict-types:sys:v01";
 
@@ -58,6 +60,14 @@ impl DisplayBaid64 for TypeSysId {
     fn to_baid64_payload(&self) -> [u8; 32] { self.to_byte_array() }
 }
 impl FromBaid64Str for TypeSysId {}
+impl TypeSysId {
+    /// Checks whether the mnemonic checksum, like the one following `#` in the alternate display
+    /// form, matches the id. The comparison ignores ASCII case.
+    pub fn verify_mnemonic(&self, mnemonic: &str) -> bool {
+        self.to_baid64_mnemonic().eq_ignore_ascii_case(mnemonic)
+    }
+}
+
 impl FromStr for TypeSysId {
     type Err = Baid64ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> { Self::from_baid64_str(s) }
@@ -66,22 +76,91 @@ impl Display for TypeSysId {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_baid64(f) }
 }
 
+fn commit_sem_ids<'a>(
+    len: u24,
+    sem_ids: impl IntoIterator<Item = &'a SemId>,
+    hasher: &mut impl CommitConsume,
+) {
+    hasher.commit_consume(len.to_le_bytes());
+    for sem_id in sem_ids {
+        sem_id.sem_commit(hasher);
+    }
+}
+
 impl SemCommit for TypeSystem {
     fn sem_commit(&self, hasher: &mut impl CommitConsume) {
-        hasher.commit_consume(self.len_u24().to_le_bytes());
-        for sem_id in self.keys() {
-            sem_id.sem_commit(hasher);
+        commit_sem_ids(self.len_u24(), self.keys(), hasher);
+    }
+}
+
+/// Hasher computing [`TypeSysId`] from a set of semantic type ids.
+///
+/// The ids are committed in their lexicographic order, such that the resulting id doesn't depend
+/// on the order in which the types were added.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TypeSystemHasher(MediumOrdSet<SemId>);
+
+impl TypeSystemHasher {
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds a semantic type id to the hashed set, returning whether it wasn't present before.
+    ///
+    /// # Errors
+    ///
+    /// If the set already contains the maximal number of types a type system may have.
+    pub fn add(&mut self, sem_id: SemId) -> Result<bool, confinement::Error> {
+        if self.0.contains(&sem_id) {
+            return Ok(false);
         }
+        self.0.push(sem_id)?;
+        Ok(true)
+    }
+
+    pub fn finish(self) -> TypeSysId {
+        let mut hasher = tagged_hasher(TYPESYS_ID_TAG);
+        commit_sem_ids(self.0.len_u24(), &self.0, &mut hasher);
+        TypeSysId::from_byte_array(hasher.finalize())
     }
 }
 
 impl TypeSystem {
     pub fn id(&self) -> TypeSysId {
-        let tag = Sha256::new_with_prefix(TYPESYS_ID_TAG).finalize();
-        let mut hasher = Sha256::new();
-        hasher.commit_consume(tag);
-        hasher.commit_consume(tag);
+        let mut hasher = tagged_hasher(TYPESYS_ID_TAG);
         self.sem_commit(&mut hasher);
         TypeSysId::from_byte_array(hasher.finalize())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::SystemBuilder;
+
+    #[test]
+    fn id_import_order() {
+        let sys1 = SystemBuilder::new()
+            .import(std_stl())
+            .unwrap()
+            .import(strict_types_stl())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        let sys2 = SystemBuilder::new()
+            .import(strict_types_stl())
+            .unwrap()
+            .import(std_stl())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        assert_eq!(sys1.id(), sys2.id());
+
+        let mut hasher = TypeSystemHasher::new();
+        for sem_id in sys1.as_types().keys().rev() {
+            assert_eq!(hasher.add(*sem_id), Ok(true));
+        }
+        let first = *sys1.as_types().keys().next().unwrap();
+        assert_eq!(hasher.add(first), Ok(false));
+        assert_eq!(hasher.finish(), sys1.id());
+    }
+}
@@ -26,8 +26,8 @@ mod id;
 mod symbols;
 mod iter;
 
-pub use id::TypeSysId;
+pub use id::{TypeSysId, TypeSystemHasher, TYPESYS_ID_TAG};
 pub use iter::{NestedCase, TypeInfo, TypeTree, TypeTreeIter};
 pub use symbols::{SymbolicSys, Symbols};
 pub use translate::{Error, SystemBuilder, TypeSymbol};
-pub use type_sys::{SymTy, TypeFqn, TypeSystem, UnknownType};
+pub use type_sys::{ExpandError, FqnParseError, SymTy, TypeFqn, TypeSystem, UnknownType};
@@ -68,7 +68,10 @@ impl Symbols {
                 present: present.clone(),
             });
         }
-        self.symbols.push(sym)?;
+        self.symbols.push(sym).map_err(|source| translate::Error::Confinement {
+            context: s!("type system has more than 16777215 type symbols"),
+            source,
+        })?;
         Ok(())
     }
 
@@ -80,6 +83,11 @@ impl Symbols {
     pub fn lookup(&self, sem_id: SemId) -> Option<&TypeFqn> {
         self.symbols.iter().find(|sym| sym.id == sem_id).and_then(|sym| sym.fqn.as_ref())
     }
+
+    /// Iterates over all named types, returning their semantic ids and fully qualified names.
+    pub fn fqns(&self) -> impl Iterator<Item = (SemId, &TypeFqn)> {
+        self.symbols.iter().filter_map(|sym| sym.fqn.as_ref().map(|fqn| (sym.id, fqn)))
+    }
 }
 
 impl Index<&'static str> for Symbols {
@@ -125,10 +133,18 @@ impl SymbolicSys {
         types: BTreeMap<SemId, SymTy>,
     ) -> Result<Self, translate::Error> {
         let mut sys = TypeSystem::new();
-        let mut sym = Symbols::with(libs)?;
+        let mut sym = Symbols::with(libs).map_err(|source| translate::Error::Confinement {
+            context: s!("type system has more than 65535 libraries"),
+            source,
+        })?;
 
         for (sem_id, info) in types {
-            sys.insert_unchecked(sem_id, info.ty)?;
+            sys.insert_unchecked(sem_id, info.ty).map_err(|source| {
+                translate::Error::Confinement {
+                    context: s!("type system has more than 16777215 types"),
+                    source,
+                }
+            })?;
             sym.update_unchecked(sem_id, info.orig)?;
         }
 
@@ -157,6 +173,10 @@ impl SymbolicSys {
 
     pub fn lookup(&self, sem_id: SemId) -> Option<&TypeFqn> { self.symbols.lookup(sem_id) }
 
+    /// Iterates over all named types of the system, returning their semantic ids and fully
+    /// qualified names.
+    pub fn fqns(&self) -> impl Iterator<Item = (SemId, &TypeFqn)> { self.symbols.fqns() }
+
     pub fn to_sem_id(&self, spec: impl Into<TypeSpec>) -> Option<SemId> {
         match spec.into() {
             TypeSpec::SemId(sem_id) => Some(sem_id),
@@ -138,8 +138,12 @@ impl SystemBuilder {
         SymbolicSys::with(self.imported_deps, self.types).map_err(|err| vec![err])
     }
 
-    fn translate_inline<Ref: LibSubref>(&mut self, inline_ty: Ty<Ref>) -> Result<SemId, Error>
-    where Ref: Translate<SemId, Context = (), Builder = SystemBuilder, Error = Error> {
+    /// Returns all imported types without checking that the imports are complete.
+    pub(crate) fn into_types(self) -> BTreeMap<SemId, SymTy> { self.types }
+
+    fn translate_inline<Ref>(&mut self, inline_ty: Ty<Ref>) -> Result<SemId, Error>
+    where Ref: LibSubref + Translate<SemId, Context = (), Builder = SystemBuilder, Error = Error>
+    {
         // compute id
         let id = inline_ty.sem_id_unnamed();
         // run for nested types
@@ -240,10 +244,40 @@ pub enum Error {
     /// library defining this type.
     InnerTypeAbsent { unknown: SemId, known: SemId },
 
-    #[from]
-    #[display(inner)]
-    Confinement(confinement::Error),
+    /// {context} ({source}).
+    Confinement {
+        context: String,
+        source: confinement::Error,
+    },
 
     /// Too deeply nested types.
     TooDeep,
 }
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::Sizing;
+
+    use super::*;
+    use crate::stl::std_stl;
+
+    #[test]
+    fn translate_all() {
+        let std = std_stl();
+        let id = |name: &'static str| std.types[&tn!(name)].sem_id_named(&tn!(name));
+        let roots = [
+            LibRef::Inline(Ty::List(InlineRef::Named(id("Bool")), Sizing::U8)),
+            LibRef::Inline(Ty::Array(InlineRef::Named(id("AlphaNum")), 32)),
+        ];
+        let expected = roots.iter().map(LibRef::id).collect::<Vec<_>>();
+
+        let mut builder = SystemBuilder::new().import(std).unwrap();
+        let ids: Vec<SemId> = LibRef::translate_all(roots, &mut builder, &()).unwrap();
+        assert_eq!(ids, expected);
+
+        let sys = builder.finalize().unwrap();
+        for id in ids {
+            assert!(sys.get(id).is_some());
+        }
+    }
+}
@@ -26,10 +26,11 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
 use std::ops::Index;
+use std::str::FromStr;
 
 use amplify::confinement::{self, Confined, MediumOrdMap};
 use amplify::num::u24;
-use encoding::{LibName, Sizing, StrictDeserialize, StrictSerialize, TypeName};
+use encoding::{InvalidRString, LibName, Sizing, StrictDeserialize, StrictSerialize, TypeName};
 use strict_encoding::STRICT_TYPES_LIB;
 
 use crate::ast::UnnamedFields;
@@ -39,6 +40,30 @@ use crate::{SemId, Ty};
 #[display("type with id `{0}` is not a part of the type system.")]
 pub struct UnknownType(SemId);
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ExpandError {
+    /// {0}
+    #[from]
+    Unknown(UnknownType),
+
+    /// type with id `{0}` references itself and can't be expanded.
+    Cycle(SemId),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum FqnParseError {
+    /// fully qualified type name `{0}` must have a form of `LibName.TypeName`.
+    NoSeparator(String),
+
+    /// invalid library or type name in a fully qualified type name: {0}
+    #[from]
+    InvalidName(InvalidRString),
+}
+
+/// Fully qualified type name, consisting of a library name and a name of the type inside that
+/// library, displayed and parsed as `LibName.TypeName`.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
@@ -58,15 +83,23 @@ impl TypeFqn {
     }
 }
 
+impl FromStr for TypeFqn {
+    type Err = FqnParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lib, name) =
+            s.split_once('.').ok_or_else(|| FqnParseError::NoSeparator(s.to_owned()))?;
+        Ok(TypeFqn {
+            lib: LibName::from_str(lib)?,
+            name: TypeName::from_str(name)?,
+        })
+    }
+}
+
 impl From<&'static str> for TypeFqn {
     fn from(value: &'static str) -> Self {
-        let Some((lib, name)) = value.split_once('.') else {
-            panic!("invalid fully qualified type name `{value}`");
-        };
-        TypeFqn {
-            lib: LibName::from(lib),
-            name: TypeName::from(name),
-        }
+        TypeFqn::from_str(value)
+            .unwrap_or_else(|err| panic!("invalid fully qualified type name `{value}`: {err}"))
     }
 }
 
@@ -152,14 +185,47 @@ impl TypeSystem {
         Ok(Self(Confined::from_collection_unsafe(extract)))
     }
 
+    /// Resolves types directly referenced by the type with a given `sem_id`, inlining a single
+    /// level of the type tree.
+    ///
+    /// Errors with [`ExpandError::Cycle`] if one of the referenced types (directly or
+    /// transitively) refers back to the expanded type.
+    pub fn expand(&self, sem_id: SemId) -> Result<BTreeMap<SemId, &Ty<SemId>>, ExpandError> {
+        let ty = self.get(sem_id).ok_or(UnknownType(sem_id))?;
+        let mut expanded = BTreeMap::new();
+        for (id, _) in ty.iter() {
+            if self.references(*id, sem_id)? {
+                return Err(ExpandError::Cycle(sem_id));
+            }
+            expanded.insert(*id, self.get(*id).ok_or(UnknownType(*id))?);
+        }
+        Ok(expanded)
+    }
+
+    fn references(&self, from: SemId, to: SemId) -> Result<bool, UnknownType> {
+        let mut queue = vec![from];
+        let mut visited = BTreeSet::new();
+        while let Some(id) = queue.pop() {
+            if id == to {
+                return Ok(true);
+            }
+            if !visited.insert(id) {
+                continue;
+            }
+            let ty = self.get(id).ok_or(UnknownType(id))?;
+            queue.extend(ty.iter().map(|(id, _)| *id));
+        }
+        Ok(false)
+    }
+
     pub(crate) fn rstring_sizing(
         &self,
         fields: &UnnamedFields<SemId>,
     ) -> Result<Option<(SemId, Sizing)>, UnknownType> {
         let rest = fields[1];
-        let rest = self.find(rest).ok_or_else(|| UnknownType(rest))?;
+        let rest = self.find(rest).ok_or(UnknownType(rest))?;
         if let Ty::List(rest, sizing) = rest {
-            let mut sizing = sizing.clone();
+            let mut sizing = *sizing;
             sizing.min += 1;
             sizing.max += 1;
             return Ok(Some((*rest, sizing)));
@@ -176,8 +242,8 @@ impl TypeSystem {
             return Ok(false);
         };
 
-        Ok(self.find(first).ok_or_else(|| UnknownType(first))?.is_char_enum()
-            && self.find(rest).ok_or_else(|| UnknownType(rest))?.is_char_enum())
+        Ok(self.find(first).ok_or(UnknownType(first))?.is_char_enum()
+            && self.find(rest).ok_or(UnknownType(rest))?.is_char_enum())
     }
 }
 
@@ -209,3 +275,55 @@ impl armor::StrictArmor for TypeSystem {
 
     fn armor_id(&self) -> Self::Id { self.id() }
 }
+
+#[cfg(test)]
+mod test {
+    use encoding::Primitive;
+
+    use super::*;
+
+    #[test]
+    fn expand() {
+        let byte = Ty::<SemId>::Primitive(Primitive::U8);
+        let byte_id = byte.sem_id_unnamed();
+        let list = Ty::list(byte_id, Sizing::ONE);
+        let list_id = list.sem_id_unnamed();
+
+        let mut sys = TypeSystem::new();
+        sys.insert_unchecked(byte_id, byte.clone()).unwrap();
+        sys.insert_unchecked(list_id, list).unwrap();
+        assert_eq!(sys.expand(list_id).unwrap(), bmap! { byte_id => &byte });
+        assert!(sys.expand(byte_id).unwrap().is_empty());
+
+        // a self-referencing type can't be produced by hashing, but may come from a malformed
+        // (deserialized) type system
+        sys.insert_unchecked(byte_id, Ty::list(list_id, Sizing::ONE)).unwrap();
+        assert_eq!(sys.expand(list_id), Err(ExpandError::Cycle(list_id)));
+    }
+
+    #[test]
+    fn fqn() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+
+        let fqn = st.fqn(&tn!("TypeName"));
+        assert_eq!(fqn.to_string(), "StrictTypes.TypeName");
+        assert_eq!(TypeFqn::from_str("StrictTypes.TypeName"), Ok(fqn));
+        assert!(matches!(TypeFqn::from_str("TypeName"), Err(FqnParseError::NoSeparator(_))));
+        assert!(matches!(TypeFqn::from_str("Std.0Bool"), Err(FqnParseError::InvalidName(_))));
+
+        let sys = crate::SystemBuilder::new()
+            .import(std.clone())
+            .unwrap()
+            .import(st.clone())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        let fqns = sys.fqns().map(|(id, fqn)| (fqn.clone(), id)).collect::<BTreeMap<_, _>>();
+        for lib in [&std, &st] {
+            for (name, ty) in &lib.types {
+                assert_eq!(fqns[&lib.fqn(name)], ty.sem_id_named(name));
+            }
+        }
+    }
+}
@@ -22,10 +22,10 @@
 
 use std::env;
 use std::fmt::{self, Display, Formatter};
-use std::str::FromStr;
+use std::str::{Chars, FromStr};
 
 use amplify::confinement::TinyVec;
-use strict_encoding::{Ident, STRICT_TYPES_LIB};
+use strict_encoding::{Ident, Primitive, Sizing, STRICT_TYPES_LIB};
 
 use crate::typelib::TypeLibId;
 use crate::SemId;
@@ -59,6 +59,141 @@ impl FromStr for StlFormat {
     }
 }
 
+/// Extension methods for [`Ident`] used for detecting names which differ only by their case
+/// and for accessing the identifier characters without depending on its inner representation.
+pub trait IdentExt {
+    /// Returns length of the identifier, which is the same in bytes and characters since
+    /// identifiers are ASCII strings.
+    fn len(&self) -> usize;
+    /// Always returns `false`, since identifiers contain at least one character; provided for
+    /// API completeness.
+    fn is_empty(&self) -> bool;
+    /// Iterates over the identifier characters.
+    fn chars(&self) -> Chars<'_>;
+
+    /// Compares two identifiers ignoring the case of the letters.
+    fn eq_ignore_ascii_case(&self, other: &Ident) -> bool;
+    /// Converts the identifier to lowercase.
+    fn to_ascii_lowercase(&self) -> Ident;
+    /// Converts the identifier to uppercase.
+    fn to_ascii_uppercase(&self) -> Ident;
+}
+
+impl IdentExt for Ident {
+    fn len(&self) -> usize { self.as_str().len() }
+
+    fn is_empty(&self) -> bool { self.as_str().is_empty() }
+
+    fn chars(&self) -> Chars<'_> { self.as_str().chars() }
+
+    fn eq_ignore_ascii_case(&self, other: &Ident) -> bool {
+        self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+
+    fn to_ascii_lowercase(&self) -> Ident {
+        Ident::try_from(self.as_str().to_ascii_lowercase())
+            .expect("identifier case change doesn't affect its validity")
+    }
+
+    fn to_ascii_uppercase(&self) -> Ident {
+        Ident::try_from(self.as_str().to_ascii_uppercase())
+            .expect("identifier case change doesn't affect its validity")
+    }
+}
+
+/// Error constructing [`Sizing`] with the minimum exceeding the maximum.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("invalid sizing: minimum {min} exceeds maximum {max}")]
+pub struct SizingError {
+    pub min: u64,
+    pub max: u64,
+}
+
+/// Extension presets for [`Sizing`] covering commonly used collection limits.
+pub trait SizingExt {
+    /// Collection which must always be empty.
+    const NONE: Sizing;
+    /// Constructs sizing checking that `min <= max`.
+    ///
+    /// Prefer this to `Sizing::new`, which doesn't check the bounds, whenever they are not
+    /// known at compile time.
+    fn try_new(min: u64, max: u64) -> Result<Sizing, SizingError>;
+    /// Collection with at least `min` and at most `u16::MAX` items.
+    fn at_least(min: u16) -> Sizing;
+    /// Collection with at most `max` items.
+    fn at_most(max: u16) -> Sizing;
+}
+
+impl SizingExt for Sizing {
+    const NONE: Sizing = Sizing::fixed(0);
+    fn try_new(min: u64, max: u64) -> Result<Sizing, SizingError> {
+        if min > max {
+            return Err(SizingError { min, max });
+        }
+        Ok(Sizing::new(min, max))
+    }
+    fn at_least(min: u16) -> Sizing { Sizing::new(min as u64, u16::MAX as u64) }
+    fn at_most(max: u16) -> Sizing { Sizing::new(0, max as u64) }
+}
+
+/// Deserializes [`Sizing`], rejecting values with the minimum exceeding the maximum, which are
+/// accepted by the `Deserialize` implementation provided by `strict_encoding`.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_sizing<'de, D>(deserializer: D) -> Result<Sizing, D::Error>
+where D: serde::Deserializer<'de> {
+    use serde::de::Error;
+    use serde::Deserialize;
+
+    let sizing = Sizing::deserialize(deserializer)?;
+    Sizing::try_new(sizing.min, sizing.max).map_err(D::Error::custom)
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("unknown primitive type name '{0}'")]
+pub struct UnknownPrimitive(String);
+
+/// Extension methods for [`Primitive`] parsing it back from its display name.
+pub trait PrimitiveExt: Sized {
+    /// Parses primitive type from the name produced by its `Display` implementation, like `U8`,
+    /// `I256`, `F16b`, `Byte` or `()`.
+    fn from_display_name(name: &str) -> Result<Self, UnknownPrimitive>;
+}
+
+impl PrimitiveExt for Primitive {
+    fn from_display_name(name: &str) -> Result<Self, UnknownPrimitive> {
+        let err = || UnknownPrimitive(name.to_owned());
+        match name {
+            "()" => return Ok(Primitive::UNIT),
+            "Byte" => return Ok(Primitive::BYTE),
+            "F16b" => return Ok(Primitive::F16B),
+            _ => {}
+        }
+        let mut chars = name.chars();
+        let cls = chars.next().ok_or_else(err)?;
+        let bits = u16::from_str(chars.as_str()).map_err(|_| err())?;
+        if bits == 0 || bits % 8 != 0 {
+            return Err(err());
+        }
+        let bytes = bits / 8;
+        // larger sizes are encoded as a factor of 16 bytes, fitting into 5 bits
+        if bytes >= 0x20 && (bytes % 16 != 0 || bytes / 16 - 2 >= 0x20) {
+            return Err(err());
+        }
+        let prim = match cls {
+            'U' => Primitive::unsigned(bytes),
+            'I' => Primitive::signed(bytes),
+            'N' => Primitive::non_zero(bytes),
+            'F' => Primitive::float(bytes),
+            _ => return Err(err()),
+        };
+        // rejects non-canonical forms like `U008`
+        if prim.to_string() != name {
+            return Err(err());
+        }
+        Ok(prim)
+    }
+}
+
 pub fn parse_args() -> (StlFormat, Option<String>) {
     let args: Vec<String> = env::args().collect();
     let ext = args.get(1).map(String::as_str).map(|s| s.trim_start_matches("--")).unwrap_or("sty");
@@ -145,6 +280,11 @@ pub enum BuildFragment {
     Digits(Ident),
 }
 
+/// Error incrementing [`SemVer`] component which has already reached `u16::MAX`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("version component {0} can't be incremented past 65535")]
+pub struct VersionOverflow(&'static str);
+
 // TODO: Manually implement Ord, PartialOrd
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
@@ -167,6 +307,32 @@ impl SemVer {
             build: none!(),
         }
     }
+
+    /// Returns next major version, resetting minor and patch numbers and dropping pre-release
+    /// and build fragments, which are superseded by the new release.
+    ///
+    /// Errors if the major version is already `u16::MAX`.
+    pub fn bump_major(&self) -> Result<Self, VersionOverflow> {
+        let major = self.major.checked_add(1).ok_or(VersionOverflow("major"))?;
+        Ok(SemVer::new(major, 0, 0))
+    }
+
+    /// Returns next minor version, resetting patch number and dropping pre-release and build
+    /// fragments.
+    ///
+    /// Errors if the minor version is already `u16::MAX`.
+    pub fn bump_minor(&self) -> Result<Self, VersionOverflow> {
+        let minor = self.minor.checked_add(1).ok_or(VersionOverflow("minor"))?;
+        Ok(SemVer::new(self.major, minor, 0))
+    }
+
+    /// Returns next patch version, dropping pre-release and build fragments.
+    ///
+    /// Errors if the patch version is already `u16::MAX`.
+    pub fn bump_patch(&self) -> Result<Self, VersionOverflow> {
+        let patch = self.patch.checked_add(1).ok_or(VersionOverflow("patch"))?;
+        Ok(SemVer::new(self.major, self.minor, patch))
+    }
 }
 
 impl Display for SemVer {
@@ -210,3 +376,111 @@ pub enum Urn {
     #[display("urn:sten:id:{0}", alt = "urn:sten:id:{0:#}")]
     Type(SemId),
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ident_case() {
+        let mixed = ident!("MixedCase_42");
+        assert_eq!(mixed.to_ascii_lowercase(), ident!("mixedcase_42"));
+        assert_eq!(mixed.to_ascii_uppercase(), ident!("MIXEDCASE_42"));
+        assert!(mixed.eq_ignore_ascii_case(&ident!("mIxEdcAsE_42")));
+        assert!(mixed.eq_ignore_ascii_case(&mixed.to_ascii_uppercase()));
+        assert!(!mixed.eq_ignore_ascii_case(&ident!("MixedCase_43")));
+        assert!(!mixed.eq_ignore_ascii_case(&ident!("MixedCase")));
+    }
+
+    #[test]
+    fn semver_bump() {
+        let mut ver = SemVer::new(1, 2, 3);
+        ver.pre.push(PreFragment::Ident(ident!("beta"))).unwrap();
+        assert_eq!(ver.bump_major().unwrap(), SemVer::new(2, 0, 0));
+        assert_eq!(ver.bump_minor().unwrap(), SemVer::new(1, 3, 0));
+        assert_eq!(ver.bump_patch().unwrap(), SemVer::new(1, 2, 4));
+
+        let max = SemVer::new(u16::MAX, u16::MAX, u16::MAX);
+        assert_eq!(max.bump_major(), Err(VersionOverflow("major")));
+        assert_eq!(max.bump_minor(), Err(VersionOverflow("minor")));
+        assert_eq!(
+            max.bump_patch().unwrap_err().to_string(),
+            "version component patch can't be incremented past 65535"
+        );
+    }
+
+    #[test]
+    fn ident_chars() {
+        let ident = ident!("beta_2");
+        assert_eq!(ident.len(), 6);
+        assert!(!ident.is_empty());
+        assert_eq!(ident.chars().rev().collect::<String>(), "2_ateb");
+    }
+
+    #[test]
+    fn primitive_from_display_name() {
+        let all = [
+            Primitive::UNIT,
+            Primitive::BYTE,
+            Primitive::F16B,
+            Primitive::U8,
+            Primitive::U16,
+            Primitive::U24,
+            Primitive::U32,
+            Primitive::U40,
+            Primitive::U48,
+            Primitive::U56,
+            Primitive::U64,
+            Primitive::U128,
+            Primitive::U160,
+            Primitive::U256,
+            Primitive::U512,
+            Primitive::U1024,
+            Primitive::I8,
+            Primitive::I16,
+            Primitive::I24,
+            Primitive::I32,
+            Primitive::I40,
+            Primitive::I48,
+            Primitive::I56,
+            Primitive::I64,
+            Primitive::I128,
+            Primitive::I256,
+            Primitive::I512,
+            Primitive::I1024,
+            Primitive::N8,
+            Primitive::N16,
+            Primitive::N24,
+            Primitive::N32,
+            Primitive::N48,
+            Primitive::N64,
+            Primitive::N128,
+            Primitive::F16,
+            Primitive::F32,
+            Primitive::F64,
+            Primitive::F80,
+            Primitive::F128,
+            Primitive::F256,
+        ];
+        for prim in all {
+            assert_eq!(Primitive::from_display_name(&prim.to_string()), Ok(prim));
+        }
+        for invalid in ["", "U", "U0", "U7", "U008", "X8", "U264", "u8", "Bytes"] {
+            assert_eq!(
+                Primitive::from_display_name(invalid),
+                Err(UnknownPrimitive(invalid.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn sizing_presets() {
+        assert_eq!(Sizing::NONE.to_string(), " ^ 0");
+        assert_eq!(Sizing::at_least(1), Sizing::U16_NONEMPTY);
+        assert_eq!(Sizing::at_least(4).to_string(), " ^ 4..");
+        assert_eq!(Sizing::at_most(u8::MAX as u16), Sizing::U8);
+        assert_eq!(Sizing::at_most(32).to_string(), " ^ ..0x20");
+        assert_eq!(Sizing::try_new(2, 2), Ok(Sizing::fixed(2)));
+        assert_eq!(Sizing::try_new(3, 2), Err(SizingError { min: 3, max: 2 }));
+    }
+}
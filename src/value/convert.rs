@@ -22,8 +22,93 @@
 
 //! Converts strict values from/to non-STON value serialization formats (JSON, YAML, TOML etc).
 
+use amplify::hex::ToHex;
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use crate::value::{EnumTag, StrictNum};
 use crate::StrictVal;
 
+/// Schema-less projection of strict values, intended for logging and other human-oriented
+/// output. Byte strings are serialized as hex strings, tuple fields are keyed by their ordinal
+/// numbers, enum values are serialized as `{ "variant": tag }` and union values as
+/// `{ "variant": tag, "value": value }`.
+///
+/// Since the projection loses type information, it is not intended to be parsed back into
+/// strict values.
+impl Serialize for StrictVal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StrictVal::Unit => serializer.serialize_unit(),
+            StrictVal::Number(num) => num.serialize(serializer),
+            StrictVal::String(s) => serializer.serialize_str(s),
+            StrictVal::Bytes(bytes) => serializer.serialize_str(&bytes.to_hex()),
+            StrictVal::Tuple(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (no, val) in fields.iter().enumerate() {
+                    map.serialize_entry(&no.to_string(), val)?;
+                }
+                map.end()
+            }
+            StrictVal::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, val) in fields {
+                    map.serialize_entry(name.as_str(), val)?;
+                }
+                map.end()
+            }
+            StrictVal::Enum(tag) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("variant", tag)?;
+                map.end()
+            }
+            StrictVal::Union(tag, val) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("variant", tag)?;
+                map.serialize_entry("value", val)?;
+                map.end()
+            }
+            StrictVal::List(items) | StrictVal::Set(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            StrictVal::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, val) in entries {
+                    map.serialize_entry(key, val)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl Serialize for StrictNum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StrictNum::Uint(v) if *v <= u64::MAX as u128 => serializer.serialize_u64(*v as u64),
+            StrictNum::Int(v) if *v >= i64::MIN as i128 && *v <= i64::MAX as i128 => {
+                serializer.serialize_i64(*v as i64)
+            }
+            // Numbers not fitting 64 bits are not supported by many formats (like JSON), so we
+            // serialize them as strings
+            num => serializer.collect_str(num),
+        }
+    }
+}
+
+impl Serialize for EnumTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            EnumTag::Name(name) => serializer.serialize_str(name.as_str()),
+            EnumTag::Ord(ord) => serializer.serialize_u8(*ord),
+        }
+    }
+}
+
 impl From<serde_json::Value> for StrictVal {
     fn from(json: serde_json::Value) -> Self {
         use serde_json::Value;
@@ -86,3 +171,39 @@ impl From<toml::Value> for StrictVal {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn json_projection() {
+        let val = StrictVal::struc([
+            ("id", StrictVal::num(42u64)),
+            ("balance", StrictVal::num(-5i64)),
+            ("supply", StrictVal::num(u128::MAX)),
+            ("hash", StrictVal::bytes([0xca, 0xfe])),
+            ("pair", StrictVal::tuple([StrictVal::str("a"), StrictVal::Unit])),
+            ("kind", StrictVal::Enum(EnumTag::Name(vname!("some")))),
+            ("ord", StrictVal::Union(EnumTag::Ord(1), Box::new(StrictVal::num(1u8)))),
+            ("items", StrictVal::list([1u8, 2u8])),
+            ("index", StrictVal::Map(vec![(StrictVal::str("k"), StrictVal::num(7u8))])),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&val).unwrap(),
+            json!({
+                "id": 42,
+                "balance": -5,
+                "supply": u128::MAX.to_string(),
+                "hash": "cafe",
+                "pair": { "0": "a", "1": null },
+                "kind": { "variant": "some" },
+                "ord": { "variant": 1, "value": 1 },
+                "items": [1, 2],
+                "index": { "k": 7 },
+            })
+        );
+    }
+}
@@ -21,15 +21,20 @@
 // limitations under the License.
 
 //! Reification module: reads & writes strict values from binary strict encodings.
+//!
+//! Strict encoding is little-endian: all multi-byte numbers, as well as length prefixes of
+//! collections, are read in little-endian byte order regardless of the target platform.
+
+use std::cmp::Ordering;
 
 use amplify::ascii::AsciiString;
 use amplify::confinement::{
-    Confined, LargeAscii, LargeBlob, LargeString, MediumAscii, MediumBlob, MediumString,
+    self, Confined, LargeAscii, LargeBlob, LargeString, MediumAscii, MediumBlob, MediumString,
     SmallAscii, SmallBlob, SmallString, TinyAscii, TinyBlob, TinyString, U16 as MAX16,
     U32 as MAX32,
 };
 use amplify::num::{u24, u40, u48, u56};
-use encoding::{DecodeError, Primitive, ReadRaw, StreamReader, StrictDecode, StrictReader};
+use encoding::{DecodeError, Primitive, ReadRaw, Sizing, StreamReader, StrictDecode, StrictReader};
 use indexmap::IndexMap;
 
 use crate::typesys::{SymbolicSys, TypeSymbol, UnknownType};
@@ -53,8 +58,16 @@ pub enum Error {
     #[from]
     Decode(DecodeError),
 
-    /// data provided to reify operation are not entirely consumed during deserialization.
-    NotEntirelyConsumed,
+    /// data provided for deserialization contain {total} bytes, while only {consumed} of them
+    /// were consumed by the type.
+    TrailingBytes { consumed: usize, total: usize },
+
+    /// key #{index} of map `{map}` can't be decoded: {source}
+    BadKey {
+        map: TypeSpec,
+        index: usize,
+        source: Box<Error>,
+    },
 }
 
 impl SymbolicSys {
@@ -79,6 +92,23 @@ impl SymbolicSys {
     }
 }
 
+#[cfg(any(test, feature = "test-helpers"))]
+impl SymbolicSys {
+    /// Test helper deserializing the data as a type matching `spec`, serializing the resulting
+    /// value back and asserting that both binary representations are equal.
+    ///
+    /// # Panics
+    ///
+    /// If the serialized value doesn't match the original `data`.
+    pub fn assert_roundtrip(&self, spec: impl Into<TypeSpec>, data: &[u8]) -> Result<(), Error> {
+        let typed = self.strict_deserialize_type(spec, data)?;
+        let mut buf = Vec::with_capacity(data.len());
+        self.as_types().strict_write_type(&typed, &mut buf).expect("in-memory writer");
+        assert_eq!(buf, data, "serialized `{}` doesn't match the source data", typed.orig);
+        Ok(())
+    }
+}
+
 impl TypeSystem {
     fn strict_read_list(
         &self,
@@ -96,25 +126,55 @@ impl TypeSystem {
 
     fn strict_read_map(
         &self,
+        map: SemId,
         len: usize,
+        sizing: Sizing,
         key_ty: SemId,
         ty: SemId,
         d: &mut impl ReadRaw,
     ) -> Result<Vec<(StrictVal, StrictVal)>, Error> {
-        let mut list = Vec::with_capacity(len);
-        for _ in 0..len {
-            let key = self.strict_read_type(key_ty, d)?;
+        if (len as u64) < sizing.min {
+            return Err(DecodeError::from(confinement::Error::Undersize {
+                len,
+                min_len: sizing.min as usize,
+            })
+            .into());
+        }
+        if (len as u64) > sizing.max {
+            return Err(DecodeError::from(confinement::Error::Oversize {
+                len,
+                max_len: sizing.max as usize,
+            })
+            .into());
+        }
+        let mut list = Vec::<(StrictVal, StrictVal)>::with_capacity(len);
+        for index in 0..len {
+            let key = self.strict_read_type(key_ty, d).map_err(|err| Error::BadKey {
+                map: TypeSpec::from(map),
+                index,
+                source: Box::new(err),
+            })?;
+            if let Some((prev, _)) = list.last() {
+                check_key_order(prev, &key.val)?;
+            }
             let item = self.strict_read_type(ty, d)?;
             list.push((key.val, item.val));
         }
         Ok(list)
     }
 
+    /// Deserializes `data` as a value of the type with the given `sem_id`, requiring all the
+    /// data to be consumed. Fails with [`Error::TrailingBytes`] if some bytes remain after the
+    /// value; for reading values from a stream use [`TypeSystem::strict_read_type`] instead.
     pub fn strict_deserialize_type(&self, sem_id: SemId, data: &[u8]) -> Result<TypedVal, Error> {
         let mut cursor = StreamReader::cursor::<MAX32>(data);
         let ty = self.strict_read_type(sem_id, &mut cursor)?;
-        if cursor.unconfine().position() as usize != data.len() {
-            return Err(Error::NotEntirelyConsumed);
+        let consumed = cursor.unconfine().position() as usize;
+        if consumed != data.len() {
+            return Err(Error::TrailingBytes {
+                consumed,
+                total: data.len(),
+            });
         }
         Ok(ty)
     }
@@ -356,31 +416,32 @@ impl TypeSystem {
             Ty::Map(key_id, id, sizing) if sizing.max <= u8::MAX as u64 => {
                 let len = u8::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(sem_id, len as usize, *sizing, *key_id, *id, d)?;
                 StrictVal::Map(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u16::MAX as u64 => {
                 let len = u16::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(sem_id, len as usize, *sizing, *key_id, *id, d)?;
                 StrictVal::Map(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u24::MAX.into_u64() => {
                 let len = u24::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len.into_usize(), *key_id, *id, d)?;
+                let list =
+                    self.strict_read_map(sem_id, len.into_usize(), *sizing, *key_id, *id, d)?;
                 StrictVal::Map(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u32::MAX as u64 => {
                 let len = u32::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(sem_id, len as usize, *sizing, *key_id, *id, d)?;
                 StrictVal::Map(list)
             }
-            Ty::Map(key_id, id, _sizing) => {
+            Ty::Map(key_id, id, sizing) => {
                 let len = u64::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(sem_id, len as usize, *sizing, *key_id, *id, d)?;
                 StrictVal::Map(list)
             }
         };
@@ -392,10 +453,48 @@ impl TypeSystem {
     }
 }
 
+/// Checks that map keys follow the strict encoding order, where it can be determined from the
+/// value itself: numbers, byte and unicode strings must be strictly ascending. For other key
+/// types only the absence of repeated keys is checked.
+fn check_key_order(prev: &StrictVal, key: &StrictVal) -> Result<(), Error> {
+    let ordered = match (prev, key) {
+        (StrictVal::Number(prev), StrictVal::Number(key)) => prev.cmp(key),
+        (StrictVal::Bytes(prev), StrictVal::Bytes(key)) => prev.cmp(key),
+        (StrictVal::String(prev), StrictVal::String(key)) => prev.cmp(key),
+        _ if prev == key => return Err(DecodeError::RepeatedMapValue.into()),
+        _ => return Ok(()),
+    };
+    match ordered {
+        Ordering::Less => Ok(()),
+        Ordering::Equal => Err(DecodeError::RepeatedMapValue.into()),
+        Ordering::Greater => Err(DecodeError::BrokenMapOrder.into()),
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use amplify::confinement::{TinyOrdMap, U16 as MAX16};
+    use encoding::StrictSerialize;
+
     use super::super::test_helpers::*;
-    // use super::*;
+    use super::*;
+    use crate::stl::std_stl;
+    use crate::typesys::SystemBuilder;
+    use crate::LibBuilder;
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Balances(TinyOrdMap<[u8; 32], u64>);
+
+    impl StrictSerialize for Balances {}
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Counter(u32);
+
+    impl StrictSerialize for Counter {}
 
     #[test]
     fn typify() {
@@ -408,4 +507,86 @@ mod test {
             r#"(name="Some name", ticker=("TICK"), precision=twoDecimals)"#
         );
     }
+
+    #[test]
+    fn roundtrip() {
+        let sys = test_system();
+        let nominal = Nominal::with("TICK", "Some name", 2);
+        let data = nominal.to_strict_serialized::<MAX16>().unwrap();
+        sys.assert_roundtrip("TestLib.Nominal", data.as_slice()).unwrap();
+    }
+
+    #[test]
+    fn byte_array_map() {
+        let std = std_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency()])
+            .transpile::<Balances>()
+            .compile()
+            .unwrap();
+        let sys =
+            SystemBuilder::new().import(lib).unwrap().import(std).unwrap().finalize().unwrap();
+
+        let balances = Balances(tiny_bmap! { [1u8; 32] => 100u64, [2u8; 32] => 200u64 });
+        let data = balances.to_strict_serialized::<MAX16>().unwrap();
+        let val = sys.strict_deserialize_type("TestLib.Balances", data.as_slice()).unwrap();
+        assert_eq!(
+            val.as_val(),
+            &svnewtype!(StrictVal::Map(vec![
+                (svbytes!(vec![1u8; 32]), svnum!(100u64)),
+                (svbytes!(vec![2u8; 32]), svnum!(200u64)),
+            ]))
+        );
+
+        // Keys must be strictly ordered
+        let mut data = vec![2u8];
+        for (key, val) in [([2u8; 32], 200u64), ([1u8; 32], 100u64)] {
+            data.extend(key);
+            data.extend(val.to_le_bytes());
+        }
+        assert_eq!(
+            sys.strict_deserialize_type("TestLib.Balances", &data).unwrap_err(),
+            Error::Decode(DecodeError::BrokenMapOrder)
+        );
+
+        // Key bytes are not sufficient
+        let mut data = vec![1u8];
+        data.extend([1u8; 16]);
+        let err = sys.strict_deserialize_type("TestLib.Balances", &data).unwrap_err();
+        let Error::BadKey {
+            index: 0, source, ..
+        } = &err
+        else {
+            panic!("unexpected error {err:?}");
+        };
+        assert!(matches!(**source, Error::Decode(_)));
+        assert!(err.to_string().ends_with(&source.to_string()));
+    }
+
+    #[test]
+    fn little_endian() {
+        let std = std_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency()])
+            .transpile::<Counter>()
+            .compile()
+            .unwrap();
+        let sys =
+            SystemBuilder::new().import(lib).unwrap().import(std).unwrap().finalize().unwrap();
+
+        let data = Counter(0x0102_0304).to_strict_serialized::<MAX16>().unwrap();
+        assert_eq!(data.as_slice(), &[4, 3, 2, 1]);
+
+        let val = sys.strict_deserialize_type("TestLib.Counter", &data).unwrap();
+        assert_eq!(val.as_val(), &svnewtype!(svnum!(0x0102_0304u32)));
+        let mut trailing = data.to_vec();
+        trailing.push(0);
+        assert_eq!(
+            sys.strict_deserialize_type("TestLib.Counter", &trailing).unwrap_err(),
+            Error::TrailingBytes {
+                consumed: 4,
+                total: 5
+            }
+        );
+        let written = sys.as_types().strict_serialize_type::<MAX16>(&val).unwrap();
+        assert_eq!(written.to_strict_serialized::<MAX16>().unwrap().as_slice(), &[4, 3, 2, 1]);
+    }
 }
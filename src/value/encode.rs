@@ -20,6 +20,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Writes strict values into binary strict encoding.
+//!
+//! Strict encoding is little-endian: all multi-byte numbers, as well as length prefixes of
+//! collections, are written in little-endian byte order regardless of the target platform.
+
 use std::io;
 
 use amplify::confinement::Confined;
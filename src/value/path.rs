@@ -132,7 +132,7 @@ impl Path {
 
     pub fn with(step: Step) -> Path { Path(small_vec!(step)) }
 
-    pub fn iter(&self) -> std::slice::Iter<Step> { self.0.iter() }
+    pub fn iter(&self) -> std::slice::Iter<'_, Step> { self.0.iter() }
 }
 
 impl<'path> IntoIterator for &'path Path {
@@ -169,37 +169,66 @@ pub enum PathError {
 }
 
 impl StrictVal {
+    /// Returns the value located at the given path, using the same path vocabulary as
+    /// [`crate::ast::Ty::at_path`] uses for the types: named and unnamed field steps select
+    /// structure and tuple fields, index steps select list and set items, and key steps select
+    /// map values.
     pub fn at_path<'p>(
         &self,
         path: impl IntoIterator<Item = &'p Step>,
     ) -> Result<&StrictVal, PathError> {
         let mut iter = path.into_iter();
-        match (self, iter.next()) {
-            (val, None) => Ok(val),
+        let val = match (self, iter.next()) {
+            (val, None) => return Ok(val),
             (StrictVal::Tuple(fields), Some(Step::UnnamedField(no)))
                 if *no as usize >= fields.len() =>
             {
-                Err(PathError::FieldNoOutOfBounds(*no, fields.len()))
+                return Err(PathError::FieldNoOutOfBounds(*no, fields.len()));
             }
-            (StrictVal::Tuple(fields), Some(Step::UnnamedField(no))) => Ok(&fields[*no as usize]),
+            (StrictVal::Tuple(fields), Some(Step::UnnamedField(no))) => &fields[*no as usize],
             (StrictVal::Struct(fields), Some(Step::NamedField(name))) => {
-                fields.get(name).ok_or(PathError::UnknownFieldName(name.clone()))
+                fields.get(name).ok_or(PathError::UnknownFieldName(name.clone()))?
             }
             (StrictVal::List(items) | StrictVal::Set(items), Some(Step::Index(idx)))
                 if *idx as usize >= items.len() =>
             {
-                Err(PathError::CollectionIndexOutOfBounds(*idx, items.len()))
+                return Err(PathError::CollectionIndexOutOfBounds(*idx, items.len()));
             }
             (StrictVal::List(items) | StrictVal::Set(items), Some(Step::Index(idx))) => {
-                Ok(&items[*idx as usize])
+                &items[*idx as usize]
             }
             (StrictVal::Map(items), Some(Step::Key(idx))) => items
                 .iter()
                 .find(|(key, _)| idx.has_match(key))
                 .map(|(_, val)| val)
-                .ok_or(PathError::UnknownKey(idx.clone())),
+                .ok_or(PathError::UnknownKey(idx.clone()))?,
 
-            (_, Some(step)) => Err(PathError::TypeMismatch(step.clone(), self.clone())),
-        }
+            (_, Some(step)) => return Err(PathError::TypeMismatch(step.clone(), self.clone())),
+        };
+        val.at_path(iter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nested_path() {
+        let block = StrictVal::struc([
+            ("header", StrictVal::struc([("height", StrictVal::num(42u32))])),
+            ("txs", StrictVal::list([StrictVal::tuple([1u8, 2u8])])),
+        ]);
+        let path = [fname!("header").into(), fname!("height").into()];
+        assert_eq!(block.at_path(&path).unwrap(), &StrictVal::num(42u32));
+
+        let path = [fname!("txs").into(), Step::Index(0), Step::UnnamedField(1)];
+        assert_eq!(block.at_path(&path).unwrap(), &StrictVal::num(2u8));
+
+        let path = [fname!("txs").into(), Step::Index(1)];
+        assert_eq!(block.at_path(&path).unwrap_err(), PathError::CollectionIndexOutOfBounds(1, 1));
+
+        let path = [fname!("header").into(), fname!("time").into()];
+        assert_eq!(block.at_path(&path).unwrap_err(), PathError::UnknownFieldName(fname!("time")));
     }
 }
@@ -27,6 +27,7 @@ use std::fmt::{self, Display, Formatter};
 use amplify::hex::ToHex;
 
 use super::StrictVal;
+use crate::ast::OPTION_NONE;
 use crate::value::EnumTag;
 
 impl StrictVal {
@@ -76,7 +77,7 @@ impl Display for StrictVal {
             }
             StrictVal::Enum(tag) => Display::fmt(tag, f),
             StrictVal::Union(tag, val)
-                if (*tag == EnumTag::Ord(0) || *tag == EnumTag::Name(vname!("none")))
+                if (*tag == EnumTag::Ord(0) || *tag == EnumTag::Name(vname!(OPTION_NONE)))
                     && **val == StrictVal::Unit =>
             {
                 f.write_str("~")
@@ -26,9 +26,11 @@ use std::fmt::Debug;
 
 // use amplify::num::apfloat::ieee;
 use amplify::num::{i1024, u1024, u24, u40, u48, u56};
-use encoding::{FieldName, StrictEnum, VariantName};
+use encoding::{FieldName, NumCls, Primitive, StrictEnum, VariantName};
 use indexmap::IndexMap;
 
+use crate::ast::{OPTION_NONE, OPTION_SOME};
+
 #[macro_export]
 macro_rules! sv {
     ($val:expr) => {
@@ -193,6 +195,79 @@ impl StrictNum {
         };
         v.try_into().expect("StrictNum is too large for the selected uint representation")
     }
+
+    /// Returns whether the number is negative and the count of significant bits in its
+    /// magnitude (for negative numbers, in its bitwise complement), not counting the sign bit.
+    fn bit_len(&self) -> (bool, u32) {
+        match self {
+            StrictNum::Uint(v) => (false, u128::BITS - v.leading_zeros()),
+            StrictNum::Int(v) if *v < 0 => (true, i128::BITS - (!*v).leading_zeros()),
+            StrictNum::Int(v) => (false, i128::BITS - v.leading_zeros()),
+            StrictNum::BigUint(v) => (false, v.bits_required() as u32),
+            StrictNum::BigInt(v) => (v.is_negative(), v.bits_required() as u32 - 1),
+        }
+    }
+
+    /// Returns the lowest 128 bits of the number in two's complement form.
+    fn low_u128(&self) -> u128 {
+        let low = |bytes: &[u8]| {
+            let mut buf = [0u8; 16];
+            buf.copy_from_slice(&bytes[..16]);
+            u128::from_le_bytes(buf)
+        };
+        match self {
+            StrictNum::Uint(v) => *v,
+            StrictNum::Int(v) => *v as u128,
+            StrictNum::BigUint(v) => low(&v.to_le_bytes()),
+            StrictNum::BigInt(v) => low(&v.to_le_bytes()),
+        }
+    }
+
+    /// Returns the number as `u128`, if it is non-negative and fits into 128 bits.
+    pub fn as_u128(&self) -> Option<u128> {
+        let (neg, len) = self.bit_len();
+        (!neg && len <= u128::BITS).then(|| self.low_u128())
+    }
+
+    /// Returns the number as `i128`, if it fits into the `i128` range.
+    pub fn as_i128(&self) -> Option<i128> {
+        let (_, len) = self.bit_len();
+        (len < i128::BITS).then(|| self.low_u128() as i128)
+    }
+
+    /// Returns the number as `u64`, if it is non-negative and fits into 64 bits.
+    pub fn as_u64(&self) -> Option<u64> { self.as_u128().and_then(|v| v.try_into().ok()) }
+
+    /// Returns the number as `i64`, if it fits into the `i64` range.
+    pub fn as_i64(&self) -> Option<i64> { self.as_i128().and_then(|v| v.try_into().ok()) }
+
+    /// Checks whether the number fits into the value range of the primitive type.
+    ///
+    /// Float primitives are not supported yet and never match.
+    pub fn fits(&self, ty: Primitive) -> bool {
+        let (neg, len) = self.bit_len();
+        if ty == Primitive::BYTE {
+            return self.fits(Primitive::U8);
+        }
+        if ty == Primitive::UNIT {
+            return false;
+        }
+        let bits = ty.byte_size() as u32 * 8;
+        match ty.info().ty {
+            NumCls::Unsigned => !neg && len <= bits,
+            NumCls::Signed => len < bits,
+            NumCls::NonZero => !neg && len > 0 && len <= bits,
+            NumCls::Float => false,
+        }
+    }
+}
+
+/// Strict value doesn't match the primitive type.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("value {found} doesn't fit primitive type {expected}")]
+pub struct PrimitiveMismatch {
+    pub expected: Primitive,
+    pub found: StrictVal,
 }
 
 /// A tag specifying enum or union variant used in strict value representation.
@@ -327,18 +402,20 @@ impl StrictVal {
         }
     }
 
+    /// Unwraps value of the canonical optional type, as produced by [`StrictVal::none`],
+    /// [`StrictVal::some`] and the decoder, accepting both ordinal and named variant tags.
     pub fn unwrap_option(&self) -> Option<&StrictVal> {
         let StrictVal::Union(tag, value) = self.skip_wrapper() else {
-            panic!("StrictVal expected to be a number but holds non-numeric value `{self}`");
+            panic!("StrictVal expected to be an optional but holds non-union value `{self}`");
         };
         match tag {
             EnumTag::Name(name)
-                if name.as_str() == "None" && value.as_ref() == &StrictVal::Unit =>
+                if name.as_str() == OPTION_NONE && value.as_ref() == &StrictVal::Unit =>
             {
                 None
             }
             EnumTag::Ord(0) if value.as_ref() == &StrictVal::Unit => None,
-            EnumTag::Name(name) if name.as_str() == "Some" => Some(value.as_ref()),
+            EnumTag::Name(name) if name.as_str() == OPTION_SOME => Some(value.as_ref()),
             EnumTag::Ord(1) => Some(value.as_ref()),
             _ => panic!("StrictVal expected to be an optional, but it is not: {self}"),
         }
@@ -356,6 +433,37 @@ impl StrictVal {
         self.unwrap_num().unwrap_uint()
     }
 
+    fn as_num(&self) -> Option<&StrictNum> {
+        match self.skip_wrapper() {
+            StrictVal::Number(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `u128`, if it is a non-negative number fitting into 128 bits.
+    pub fn as_u128(&self) -> Option<u128> { self.as_num()?.as_u128() }
+
+    /// Returns the value as `i128`, if it is a number fitting into the `i128` range.
+    pub fn as_i128(&self) -> Option<i128> { self.as_num()?.as_i128() }
+
+    /// Returns the value as `u64`, if it is a non-negative number fitting into 64 bits.
+    pub fn as_u64(&self) -> Option<u64> { self.as_num()?.as_u64() }
+
+    /// Returns the value as `i64`, if it is a number fitting into the `i64` range.
+    pub fn as_i64(&self) -> Option<i64> { self.as_num()?.as_i64() }
+
+    /// Returns the number held by the value, checking it against the value range of the
+    /// primitive type declared by the schema.
+    pub fn try_as_primitive(&self, ty: Primitive) -> Result<StrictNum, PrimitiveMismatch> {
+        match self.as_num() {
+            Some(num) if num.fits(ty) => Ok(*num),
+            _ => Err(PrimitiveMismatch {
+                expected: ty,
+                found: self.clone(),
+            }),
+        }
+    }
+
     pub fn unwrap_string(&self) -> String {
         match self.skip_wrapper() {
             StrictVal::String(v) => v.clone(),
@@ -463,7 +571,7 @@ impl<T: Into<StrictVal>> From<Option<T>> for StrictVal {
 
 #[cfg(test)]
 mod test {
-    // use super::*;
+    use super::*;
 
     #[test]
     fn construct() {
@@ -480,4 +588,40 @@ mod test {
             r#"Struct({FieldName("name"): String("Some name"), FieldName("ticker"): String("TICK"), FieldName("precision"): Number(Uint(8))})"#
         )
     }
+
+    #[test]
+    fn options() {
+        assert_eq!(svnone!().unwrap_option(), None);
+        assert_eq!(svsome!(5u8).unwrap_option(), Some(&svnum!(5u8)));
+        assert_eq!(svunion!(none => ()).unwrap_option(), None);
+        assert_eq!(svunion!(some => 5u8).unwrap_option(), Some(&svnum!(5u8)));
+    }
+
+    #[test]
+    fn numeric_accessors() {
+        assert_eq!(svnum!(8u8).as_u64(), Some(8));
+        assert_eq!(svnewtype!(svnum!(8u8)).as_i64(), Some(8));
+        assert_eq!(svnum!(-1i8).as_u64(), None);
+        assert_eq!(svnum!(-1i8).as_i128(), Some(-1));
+        assert_eq!(svnum!(u128::MAX).as_u64(), None);
+        assert_eq!(svnum!(u128::MAX).as_i128(), None);
+        assert_eq!(svnum!(u1024::from(u128::MAX)).as_u128(), Some(u128::MAX));
+        assert_eq!(svnum!(u1024::from(u128::MAX) + u1024::from(1u8)).as_u128(), None);
+        assert_eq!(svnum!(i1024::from(i128::MIN)).as_i128(), Some(i128::MIN));
+        assert_eq!(svnum!(i1024::from(-1i8)).as_u128(), None);
+        assert_eq!(svstr!("8").as_u64(), None);
+
+        assert_eq!(svnum!(255u8).try_as_primitive(Primitive::U8), Ok(StrictNum::Uint(255)));
+        assert_eq!(svnum!(255u8).try_as_primitive(Primitive::BYTE), Ok(StrictNum::Uint(255)));
+        assert_eq!(svnum!(-128i8).try_as_primitive(Primitive::I8), Ok(StrictNum::Int(-128)));
+        let err = svnum!(256u16).try_as_primitive(Primitive::U8).unwrap_err();
+        assert_eq!(err.expected, Primitive::U8);
+        assert!(svnum!(128u8).try_as_primitive(Primitive::I8).is_err());
+        assert!(svnum!(-129i16).try_as_primitive(Primitive::I8).is_err());
+        assert!(svnum!(-1i8).try_as_primitive(Primitive::U64).is_err());
+        assert!(svnum!(0u8).try_as_primitive(Primitive::N8).is_err());
+        assert!(svnum!(1u8).try_as_primitive(Primitive::N8).is_ok());
+        assert!(svnum!(1u8).try_as_primitive(Primitive::F32).is_err());
+        assert!(svstr!("1").try_as_primitive(Primitive::U8).is_err());
+    }
 }
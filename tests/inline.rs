@@ -0,0 +1,100 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate strict_types;
+
+use amplify::confinement::{TinyOrdMap, TinyVec};
+use strict_encoding::{StrictDecode, StrictEncode, StrictType};
+use strict_types::typelib::{InlineRef, InlineRef1, InlineRef2};
+use strict_types::{CompileError, LibBuilder, LibRef, Ty, TypeLib};
+
+const LIB: &str = "Test";
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB)]
+pub struct Leaf {
+    pub index: TinyOrdMap<u8, u16>,
+}
+
+/// Field type is a map (level 1) of lists (level 2) of lists (level 3) of a named type.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB)]
+pub struct Deep {
+    pub leaves: TinyOrdMap<u8, TinyVec<TinyVec<Leaf>>>,
+}
+
+/// One more level of inline nesting than supported.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB)]
+pub struct TooDeep {
+    pub leaves: TinyOrdMap<u8, TinyVec<TinyVec<TinyVec<Leaf>>>>,
+}
+
+fn level3(lib: &TypeLib) -> &Ty<InlineRef2> {
+    let Some(Ty::Struct(fields)) = lib.types.get(&tn!("Deep")) else {
+        panic!("Deep is not a struct")
+    };
+    let Some(LibRef::Inline(Ty::Map(_, InlineRef::Inline(level2), _))) =
+        fields.first().map(|f| &f.ty)
+    else {
+        panic!("Deep.leaves is not an inline map")
+    };
+    let Ty::List(InlineRef1::Inline(level3), _) = level2 else {
+        panic!("map value is not an inline list")
+    };
+    level3
+}
+
+#[test]
+fn three_level_roundtrip() {
+    let lib = LibBuilder::new(libname!(LIB), None).transpile::<Deep>().compile().unwrap();
+    let leaf = lib.types.get(&tn!("Leaf")).unwrap().sem_id_named(&tn!("Leaf"));
+    let Ty::List(InlineRef2::Named(id), _) = level3(&lib) else {
+        panic!("list item is not a named type")
+    };
+    assert_eq!(*id, leaf);
+
+    let deep = lib.types.get(&tn!("Deep")).unwrap();
+    assert_eq!(deep.to_string(), format!("leaves {{U8 -> ^ ..0xff [[{leaf} ^ ..0xff] ^ ..0xff]}}"));
+    let sem_id = deep.sem_id_named(&tn!("Deep"));
+
+    let data = lib.to_versioned_serialized().unwrap();
+    let decoded = TypeLib::from_versioned_serialized(&data).unwrap();
+    assert_eq!(decoded, lib);
+    assert_eq!(decoded.id(), lib.id());
+    assert_eq!(decoded.types.get(&tn!("Deep")).unwrap().sem_id_named(&tn!("Deep")), sem_id);
+    assert_eq!(level3(&decoded).sem_id_unnamed(), level3(&lib).sem_id_unnamed());
+
+    let parsed = TypeLib::from_source(&lib.to_string()).unwrap();
+    assert_eq!(parsed, lib);
+    assert_eq!(parsed.to_string(), lib.to_string());
+}
+
+#[test]
+fn four_levels_rejected() {
+    let err = LibBuilder::new(libname!(LIB), None).transpile::<TooDeep>().compile().unwrap_err();
+    assert!(matches!(err, CompileError::NestedInline(name, ..) if name == tn!("TooDeep")));
+}
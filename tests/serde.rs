@@ -0,0 +1,143 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate strict_encoding;
+
+use strict_encoding::stl::Bool;
+use strict_encoding::{Ident, Sizing, TypeName};
+use strict_types::stl::{std_stl, strict_types_stl};
+use strict_types::{LibBuilder, SemId, Ty, TypeLib};
+
+#[derive(Clone, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = "TestLib")]
+struct Order {
+    id: u64,
+    flag: Bool,
+    code: [u8; 2],
+    price: Option<u16>,
+}
+
+#[test]
+fn yaml_golden() {
+    let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+        .transpile::<Order>()
+        .compile()
+        .unwrap();
+    let golden = include_str!("data/order.yaml");
+    assert_eq!(serde_yaml::to_string(&lib).unwrap(), golden);
+    assert_eq!(serde_yaml::from_str::<TypeLib>(golden).unwrap(), lib);
+}
+
+#[test]
+fn yaml_roundtrip() {
+    for lib in [std_stl(), strict_types_stl()] {
+        let yaml = serde_yaml::to_string(&lib).unwrap();
+        assert_eq!(serde_yaml::from_str::<TypeLib>(&yaml).unwrap(), lib);
+    }
+}
+
+/// Converts adjacently tagged type references into the externally tagged representation used by
+/// the earlier versions of the crate.
+fn legacy_refs(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(mut map) if map.len() == 2 && map.contains_key("type") => {
+            let Some(Value::String(tag)) = map.remove("type") else {
+                panic!("non-string reference tag")
+            };
+            let inner = legacy_refs(map.remove("value").expect("reference without value"));
+            Value::Object([(tag, inner)].into_iter().collect())
+        }
+        Value::Object(map) => {
+            Value::Object(map.into_iter().map(|(k, v)| (k, legacy_refs(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(legacy_refs).collect()),
+        value => value,
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = "TestLib")]
+struct Item {
+    id: u64,
+    flag: Bool,
+    code: [u8; 2],
+}
+
+#[test]
+fn legacy_refs_json() {
+    let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+        .transpile::<Item>()
+        .compile()
+        .unwrap();
+    let json = serde_json::to_value(&lib).unwrap();
+    let legacy = legacy_refs(json.clone());
+    assert!(legacy.to_string().contains(r#"{"inline":{"Primitive":8}}"#));
+    assert!(legacy.to_string().contains(r#"{"extern":{"libId":"#));
+    assert_eq!(serde_json::from_value::<TypeLib>(json).unwrap(), lib);
+    assert_eq!(serde_json::from_value::<TypeLib>(legacy).unwrap(), lib);
+}
+
+#[derive(Clone, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = "TestLib")]
+struct Flagged {
+    flag: Bool,
+}
+
+#[test]
+fn legacy_refs_yaml() {
+    let lib = LibBuilder::new(libname!("TestLib"), [std_stl().to_dependency()])
+        .transpile::<Flagged>()
+        .compile()
+        .unwrap();
+    let yaml = serde_yaml::to_string(&lib).unwrap();
+    let legacy = yaml.replace("ty:\n      type: extern\n      value:\n", "ty: !extern\n");
+    assert_ne!(legacy, yaml);
+    assert_eq!(serde_yaml::from_str::<TypeLib>(&legacy).unwrap(), lib);
+}
+
+#[test]
+fn ident_validation() {
+    assert_eq!(serde_json::from_str::<Ident>("\"abc_1\"").unwrap(), ident!("abc_1"));
+    assert!(serde_json::from_str::<Ident>("\"1abc\"").is_err());
+    assert!(serde_json::from_str::<Ident>("\"a-b\"").is_err());
+    assert!(serde_json::from_str::<TypeName>("\"order\"").is_err());
+
+    let malformed = include_str!("data/order.yaml").replace("Order:", "1Order:");
+    assert!(serde_yaml::from_str::<TypeLib>(&malformed).is_err());
+}
+
+#[test]
+fn sizing_validation() {
+    let valid = Ty::<SemId>::List(SemId::default(), Sizing::new(5, 10));
+    let yaml = serde_yaml::to_string(&valid).unwrap();
+    assert_eq!(serde_yaml::from_str::<Ty<SemId>>(&yaml).unwrap(), valid);
+
+    let invalid = Ty::<SemId>::Map(SemId::default(), SemId::default(), Sizing::new(10, 5));
+    let yaml = serde_yaml::to_string(&invalid).unwrap();
+    let err = serde_yaml::from_str::<Ty<SemId>>(&yaml).unwrap_err();
+    assert!(err.to_string().contains("minimum 10 exceeds maximum 5"));
+}